@@ -5,9 +5,9 @@
 /// returns `true` for the configured selector.  If the condition is not met
 /// within `condition_timeout_ledgers`, the buyer may reclaim via
 /// `refund_conditional_failed`.
-use soroban_sdk::{contracttype, symbol_short, token, Address, Bytes, Env};
+use soroban_sdk::{contracttype, symbol_short, token, Address, Bytes, Env, Vec};
 
-use crate::{DataKey, Error, Session, SessionStatus, SkillSyncContract};
+use crate::{Error, Session, SessionStatus, SkillSyncContract};
 
 // ── Storage key ───────────────────────────────────────────────────────────────
 
@@ -63,13 +63,16 @@ impl SkillSyncContract {
         condition_timeout_ledgers: u32,
     ) -> Result<(), Error> {
         Self::require_not_paused(&env)?;
+        payer.require_auth();
         crate::acquire_lock(&env)?;
 
         crate::validate_session_id(&session_id)?;
         crate::validate_amount(amount)?;
+        crate::validate_amount_limits(&env, amount)?;
         crate::validate_different_addresses(&payer, &payee)?;
+        crate::validate_asset(&env, &asset)?;
 
-        let fee_bps = Self::get_platform_fee(env.clone());
+        let fee_bps = Self::get_payee_fee(env.clone(), payee.clone());
         let now = env.ledger().timestamp();
         let dispute_window_ledgers = Self::get_dispute_window(env.clone());
         let current_ledger = env.ledger().sequence();
@@ -98,7 +101,7 @@ impl SkillSyncContract {
             .ok_or(Error::FeeCalculationOverflow)?;
 
         let session = Session {
-            version: crate::VERSION,
+            version: crate::SESSION_VERSION,
             session_id: session_id.clone(),
             payer: payer.clone(),
             payee: payee.clone(),
@@ -119,9 +122,13 @@ impl SkillSyncContract {
             resolver: None,
             resolution_note: None,
             pending_extension: None,
+            metadata_hash: None,
+            payout_splits: Vec::new(&env),
         };
 
-        Self::put_session(env.clone(), session.clone())?;
+        crate::insert_session(&env, &session)?;
+        Self::add_to_party_index(&env, &session);
+        Self::record_session_locked(&env, &session);
         Self::add_to_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
 
         let contract_id = env.current_contract_address();
@@ -138,7 +145,15 @@ impl SkillSyncContract {
 
         env.events().publish(
             (symbol_short!("cond_lock"),),
-            (session_id, payer, payee, amount),
+            crate::ConditionalFundsLocked {
+                version: crate::EVENT_VERSION,
+                session_id,
+                payer,
+                payee,
+                amount,
+                condition_contract: config.condition_contract,
+                timeout_ledger: config.timeout_ledger,
+            },
         );
 
         crate::release_lock(&env);
@@ -201,34 +216,35 @@ impl SkillSyncContract {
             .ok_or(Error::FeeCalculationOverflow)?
             .checked_div(10_000)
             .ok_or(Error::FeeCalculationOverflow)?;
-        let payout = session
-            .amount
-            .checked_sub(fee)
-            .ok_or(Error::FeeCalculationOverflow)?;
-
-        let token_client = token::Client::new(&env, &session.asset);
-        let contract_id = env.current_contract_address();
-        let treasury = Self::get_treasury(env.clone());
-
-        if payout > 0 {
-            token_client.transfer(&contract_id, &session.payee, &payout);
-        }
-        if fee > 0 {
-            token_client.transfer(&contract_id, &treasury, &fee);
-        }
+        // The fee was escrowed on top of the amount.
+        let payout = session.amount;
 
         let now = env.ledger().timestamp();
         session.status = SessionStatus::Approved;
         session.updated_at = now;
         session.approved_at = now;
 
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
+        crate::save_session(&env, &session);
         Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
         env.storage()
             .persistent()
             .remove(&ConditionalKey::Config(session_id.clone()));
 
+        let token_client = token::Client::new(&env, &session.asset);
+        let contract_id = env.current_contract_address();
+        let treasury = Self::get_treasury(env.clone());
+
+        let legs = if payout > 0 {
+            Self::pay_payees(&env, &token_client, &session, payout)?
+        } else {
+            Vec::new(&env)
+        };
+        crate::record_completion_receipt(&env, &session, payout, fee, &treasury, legs);
+
+        if fee > 0 {
+            token_client.transfer(&contract_id, &treasury, &fee);
+        }
+
         env.events().publish(
             (symbol_short!("cond_met"),),
             ConditionMetEvent {
@@ -242,9 +258,8 @@ impl SkillSyncContract {
     }
 
     /// Buyer reclaims funds when the condition was not met within the timeout.
+    /// Remains available while the contract is paused.
     pub fn refund_conditional_failed(env: Env, session_id: Bytes) -> Result<(), Error> {
-        Self::require_not_paused(&env)?;
-
         let config: ConditionalConfig = env
             .storage()
             .persistent()
@@ -273,21 +288,20 @@ impl SkillSyncContract {
             .checked_add(fee)
             .ok_or(Error::FeeCalculationOverflow)?;
 
-        let token_client = token::Client::new(&env, &session.asset);
-        let contract_id = env.current_contract_address();
-        token_client.transfer(&contract_id, &session.payer, &total_locked);
-
         let now = env.ledger().timestamp();
         session.status = SessionStatus::Refunded;
         session.updated_at = now;
 
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
+        crate::save_session(&env, &session);
         Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
         env.storage()
             .persistent()
             .remove(&ConditionalKey::Config(session_id.clone()));
 
+        let token_client = token::Client::new(&env, &session.asset);
+        let contract_id = env.current_contract_address();
+        token_client.transfer(&contract_id, &session.payer, &total_locked);
+
         env.events().publish(
             (symbol_short!("cond_fail"),),
             ConditionFailedRefundEvent {
@@ -6,7 +6,7 @@
 /// admin may fall back to the standard `resolve_dispute` path.
 use soroban_sdk::{contracttype, symbol_short, token, Address, Bytes, Env, Symbol};
 
-use crate::{DataKey, Error, SessionStatus, SkillSyncContract};
+use crate::{Error, SessionStatus, SkillSyncContract};
 
 // ── Storage keys ──────────────────────────────────────────────────────────────
 
@@ -169,6 +169,18 @@ impl SkillSyncContract {
             .checked_div(10_000)
             .ok_or(Error::FeeCalculationOverflow)?;
 
+        let now = env.ledger().timestamp();
+        session.status = SessionStatus::Resolved;
+        session.updated_at = now;
+        session.resolved_at = now;
+        session.resolver = Some(dao_address);
+
+        crate::save_session(&env, &session);
+        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
+        env.storage()
+            .persistent()
+            .remove(&DaoKey::Proposal(session_id.clone()));
+
         let token_client = token::Client::new(&env, &session.asset);
         let contract_id = env.current_contract_address();
         let treasury = Self::get_treasury(env.clone());
@@ -177,25 +189,12 @@ impl SkillSyncContract {
             token_client.transfer(&contract_id, &session.payer, &buyer_share);
         }
         if seller_share > 0 {
-            token_client.transfer(&contract_id, &session.payee, &seller_share);
+            Self::pay_payees(&env, &token_client, &session, seller_share)?;
         }
         if fee > 0 {
             token_client.transfer(&contract_id, &treasury, &fee);
         }
 
-        let now = env.ledger().timestamp();
-        session.status = SessionStatus::Resolved;
-        session.updated_at = now;
-        session.resolved_at = now;
-        session.resolver = Some(dao_address);
-
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
-        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
-        env.storage()
-            .persistent()
-            .remove(&DaoKey::Proposal(session_id.clone()));
-
         env.events().publish(
             (symbol_short!("dao_done"),),
             DisputeResolvedByDAOEvent {
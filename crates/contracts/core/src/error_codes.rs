@@ -81,6 +81,8 @@ pub enum TimeoutDisputeError {
     DisputeNotOpen = 502,
     /// Session is not eligible for resolution.
     ResolutionNotAllowed = 503,
+    /// Dispute raised or extended after the dispute window closed.
+    DisputeWindowElapsed = 504,
 }
 
 /// Errors for contract upgrade failures (issue #160).
@@ -102,3 +104,16 @@ pub enum ReentrancyError {
     /// A reentrant call was detected. Code 700 per spec.
     ReentrancyDetected = 700,
 }
+
+/// Errors for session package operations.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum PackageError {
+    /// Package ID does not exist.
+    PackageNotFound = 800,
+    /// Number of sessions is zero or above MAX_PACKAGE_SESSIONS.
+    InvalidPackageSize = 801,
+    /// Package is already completed or cancelled.
+    PackageNotActive = 802,
+}
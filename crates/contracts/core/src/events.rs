@@ -21,7 +21,7 @@ pub struct DisputeResolved {
     pub timestamp: u64,
 }
 
-/// Emitted when the admin changes the treasury wallet (issue #152).
+/// Emitted when the treasury wallet is changed (issue #152).
 ///
 /// Emitted during `set_treasury()`.
 #[contracttype]
@@ -31,10 +31,38 @@ pub struct TreasuryUpdated {
     pub old_treasury: Address,
     /// New treasury address.
     pub new_treasury: Address,
-    /// Address of the admin who performed the update.
+    /// Admin or treasurer who performed the update.
     pub updated_by: Address,
 }
 
+/// Emitted when the admin grants a role to an account.
+///
+/// Emitted during `grant_role()`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct RoleGranted {
+    /// Role identifier, e.g. `PAUSER_ROLE`.
+    pub role: Bytes,
+    /// Account that now holds the role.
+    pub account: Address,
+    /// Address of the admin who granted the role.
+    pub granted_by: Address,
+}
+
+/// Emitted when the admin revokes a role from an account.
+///
+/// Emitted during `revoke_role()`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct RoleRevoked {
+    /// Role identifier, e.g. `PAUSER_ROLE`.
+    pub role: Bytes,
+    /// Account that no longer holds the role.
+    pub account: Address,
+    /// Address of the admin who revoked the role.
+    pub revoked_by: Address,
+}
+
 /// Emitted when the contract WASM is upgraded (issue #153).
 ///
 /// Allows off-chain monitoring of contract upgrades.
@@ -75,6 +103,8 @@ pub struct OffchainApprovalExecuted {
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct SessionApprovedEvent {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
     /// Identifier of the session that was approved.
     pub session_id: Bytes,
     /// Address of the buyer who approved.
@@ -122,3 +152,143 @@ pub struct DisputeWindowUpdated {
     /// Ledger timestamp at the moment of the update.
     pub timestamp: u64,
 }
+
+/// Emitted when the admin adds or removes a token from the escrow allowlist.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct TokenAllowlistUpdated {
+    /// Token contract address.
+    pub asset: Address,
+    /// `true` if the token was added, `false` if removed.
+    pub allowed: bool,
+    /// Address of the admin who performed the update.
+    pub updated_by: Address,
+}
+
+/// Emitted when the admin sets or clears a payee's fee override.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct FeeOverrideUpdated {
+    /// Payee whose sessions the override applies to.
+    pub payee: Address,
+    /// Fee charged on the payee's new sessions; `None` when cleared.
+    pub fee_bps: Option<u32>,
+    /// Address of the admin who performed the update.
+    pub updated_by: Address,
+}
+
+/// Emitted when the admin updates the per-session amount limits.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct LimitsUpdated {
+    /// Previous minimum session amount.
+    pub old_min: i128,
+    /// Previous maximum session amount.
+    pub old_max: i128,
+    /// New minimum session amount.
+    pub new_min: i128,
+    /// New maximum session amount.
+    pub new_max: i128,
+    /// Address of the admin who performed the update.
+    pub updated_by: Address,
+}
+
+/// Layout version carried by the versioned event structs below. Bumped
+/// whenever a field is added, removed or changes meaning.
+pub const EVENT_VERSION: u32 = 1;
+
+/// Emitted once when the contract is initialized.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Initialized {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    /// Address configured as admin.
+    pub admin: Address,
+    /// Initial platform fee in basis points.
+    pub platform_fee_bps: u32,
+    /// Treasury that receives platform fees.
+    pub treasury: Address,
+    /// Dispute window in ledgers.
+    pub dispute_window_ledgers: u32,
+    /// Contract storage version.
+    pub contract_version: u32,
+}
+
+/// Emitted when a buyer escrows funds for a session.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct FundsLocked {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    /// Identifier of the new session.
+    pub session_id: Bytes,
+    /// Buyer who locked the funds.
+    pub payer: Address,
+    /// Seller who will be paid on approval.
+    pub payee: Address,
+    /// Token the session is escrowed in.
+    pub asset: Address,
+    /// Session amount, excluding the fee.
+    pub amount: i128,
+    /// Platform fee locked alongside the amount.
+    pub fee: i128,
+}
+
+/// Emitted when a buyer escrows funds for a milestone-based session.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct FundsLockedWithMilestones {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    /// Identifier of the new session.
+    pub session_id: Bytes,
+    /// Buyer who locked the funds.
+    pub payer: Address,
+    /// Seller who is paid as milestones are released.
+    pub payee: Address,
+    /// Token the session is escrowed in.
+    pub asset: Address,
+    /// Sum of all milestone amounts.
+    pub total_amount: i128,
+    /// Platform fee locked alongside the amount.
+    pub fee: i128,
+    /// Number of milestones in the session.
+    pub milestone_count: u32,
+}
+
+/// Emitted when a buyer escrows funds released by an external condition.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ConditionalFundsLocked {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    /// Identifier of the new session.
+    pub session_id: Bytes,
+    /// Buyer who locked the funds.
+    pub payer: Address,
+    /// Seller who is paid once the condition is met.
+    pub payee: Address,
+    /// Session amount, excluding the fee.
+    pub amount: i128,
+    /// Contract queried for the release condition.
+    pub condition_contract: Address,
+    /// Ledger after which the condition can no longer release funds.
+    pub timeout_ledger: u32,
+}
+
+/// Emitted when the seller marks a session as delivered.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SessionCompleted {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    /// Identifier of the completed session.
+    pub session_id: Bytes,
+    /// Seller who completed the session.
+    pub payee: Address,
+    /// Session amount awaiting approval.
+    pub amount: i128,
+    /// Ledger timestamp at the moment of completion.
+    pub timestamp: u64,
+}
@@ -3,7 +3,7 @@
 /// Buyers may pay an optional premium when locking funds.  If a dispute
 /// resolution awards the buyer less than 80 % of the session amount, the
 /// insurance pool covers the shortfall up to 100 %.
-use soroban_sdk::{contracttype, symbol_short, token, Address, Bytes, Env};
+use soroban_sdk::{contracttype, symbol_short, token, Address, Bytes, Env, Vec};
 
 use crate::{DataKey, Error, Session, SessionStatus, SkillSyncContract};
 
@@ -138,18 +138,21 @@ impl SkillSyncContract {
         premium_bps: u32,
     ) -> Result<(), Error> {
         Self::require_not_paused(&env)?;
+        payer.require_auth();
         crate::acquire_lock(&env)?;
 
         crate::validate_session_id(&session_id)?;
         crate::validate_amount(amount)?;
+        crate::validate_amount_limits(&env, amount)?;
         crate::validate_different_addresses(&payer, &payee)?;
+        crate::validate_asset(&env, &asset)?;
 
         if premium_bps > 10_000 {
             crate::release_lock(&env);
             return Err(Error::InvalidFeeBps);
         }
 
-        let fee_bps = Self::get_platform_fee(env.clone());
+        let fee_bps = Self::get_payee_fee(env.clone(), payee.clone());
         let now = env.ledger().timestamp();
         let dispute_window_ledgers = Self::get_dispute_window(env.clone());
         let current_ledger = env.ledger().sequence();
@@ -181,7 +184,7 @@ impl SkillSyncContract {
 
         // Store the session via the standard path.
         let session = Session {
-            version: crate::VERSION,
+            version: crate::SESSION_VERSION,
             session_id: session_id.clone(),
             payer: payer.clone(),
             payee: payee.clone(),
@@ -202,9 +205,13 @@ impl SkillSyncContract {
             resolver: None,
             resolution_note: None,
             pending_extension: None,
+            metadata_hash: None,
+            payout_splits: Vec::new(&env),
         };
 
-        Self::put_session(env.clone(), session.clone())?;
+        crate::insert_session(&env, &session)?;
+        Self::add_to_party_index(&env, &session);
+        Self::record_session_locked(&env, &session);
         Self::add_to_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
 
         let contract_id = env.current_contract_address();
@@ -239,7 +246,15 @@ impl SkillSyncContract {
 
         env.events().publish(
             (symbol_short!("locked"),),
-            (session_id, payer, payee, amount, platform_fee),
+            crate::FundsLocked {
+                version: crate::EVENT_VERSION,
+                session_id,
+                payer,
+                payee,
+                asset,
+                amount,
+                fee: platform_fee,
+            },
         );
 
         crate::release_lock(&env);
@@ -300,14 +315,14 @@ impl SkillSyncContract {
             return Err(Error::InsufficientBalance);
         }
 
-        let token_client = token::Client::new(&env, &record.asset);
-        let contract_id = env.current_contract_address();
-        token_client.transfer(&contract_id, &record.buyer, &payout);
-
         set_pool_balance(&env, &record.asset, pool_bal - payout);
         record.claimed = true;
         env.storage().persistent().set(&key, &record);
 
+        let token_client = token::Client::new(&env, &record.asset);
+        let contract_id = env.current_contract_address();
+        token_client.transfer(&contract_id, &record.buyer, &payout);
+
         env.events().publish(
             (symbol_short!("ins_paid"),),
             InsuranceClaimPaidEvent {
@@ -1,25 +1,35 @@
 #![no_std]
 
+#[macro_use]
+mod val_codec;
+
 pub mod conditional_escrow;
 pub mod dao_dispute;
 pub mod insurance;
+pub mod packages;
 pub mod storage_archive;
 
 pub mod error_codes;
 
-pub use error_codes::{AuthError, FinancialError, InitError, ReentrancyError, SessionError, TimeoutDisputeError, UpgradeError};
+pub use error_codes::{
+    AuthError, FinancialError, InitError, PackageError, ReentrancyError, SessionError,
+    TimeoutDisputeError, UpgradeError,
+};
 // pub mod errors;  // Not used - using Error enum in lib.rs instead
 pub mod events;
 pub mod oracle;
+pub mod rbac;
 
 pub use events::{
-    ContractUpgraded, DisputeResolved, DisputeWindowUpdated, OffchainApprovalExecuted, ReferrerFeePaid,
-    SessionApprovedEvent, TreasuryUpdated,
+    ConditionalFundsLocked, ContractUpgraded, DisputeResolved, DisputeWindowUpdated, FeeOverrideUpdated,
+    FundsLocked, FundsLockedWithMilestones, Initialized, LimitsUpdated, OffchainApprovalExecuted,
+    ReferrerFeePaid, RoleGranted, RoleRevoked, SessionApprovedEvent, SessionCompleted, TokenAllowlistUpdated, TreasuryUpdated,
+    EVENT_VERSION,
 };
 
 use soroban_sdk::{
     contract, contracterror, contractimpl, contracttype, panic_with_error, token, Address, Bytes,
-    BytesN, Env, Symbol, Vec,
+    BytesN, Env, Map, Symbol, TryFromVal, Val, Vec,
 };
 
 pub const DISPUTE_WINDOW_MIN_SECONDS: u64 = 60;
@@ -40,6 +50,9 @@ pub const MAX_SESSION_ID_LEN: u32 = 64; // Max session ID length
 pub const MAX_NOTE_LEN: u32 = 256; // Max resolution note length
 pub const MAX_AMOUNT: i128 = 1_000_000_000_000_000; // 100 trillion units max
 pub const MAX_EXTENSION_LEDGERS: u64 = 10_000; // Maximum extension duration in ledgers
+pub const MAX_PAGE_LIMIT: u32 = 100; // Max items returned by paginated getters
+pub const MAX_PAYOUT_SPLITS: u32 = 5; // Max payees sharing one session payout
+pub const MAX_DISPUTE_EXTENSION_LEDGERS: u64 = 17_280; // Max total party extension of the dispute deadline (~1 day)
 
 // Issue #208: Maximum session duration enforcement
 pub const DEFAULT_MAX_SESSION_DURATION_LEDGERS: u32 = 30_000; // ~7 days
@@ -58,13 +71,18 @@ enum DataKey {
     PlatformFee,
     Treasury,
     Version,
+    // Legacy session key (raw id); read as a fallback, removed by migration
     Session(Bytes),
+    // Sessions keyed by SHA-256 of the session id, see `derive_session_key`
+    SessionByKey(BytesN<32>),
     // Expiry index: groups sessions by expiry day bucket (timestamp / SECONDS_PER_DAY)
     ExpiryIndex(u64),
     // Track which day buckets have been processed for pagination
     LastProcessedExpiryBucket,
     // Upgradeability storage keys
     PendingUpgrade,
+    UpgradeTimelock,
+    CurrentWasmHash,
     // Fee configuration
     FeeOnRefunds,
     // Reputation system
@@ -87,6 +105,28 @@ enum DataKey {
     UserRating(Address),
     // Issue #211: Per-session per-user rating flag (session_id, rater)
     RatingFlag(Bytes, Address),
+    // Session ids per party, in creation order: one entry per (party, position)
+    PayerSessionCount(Address),
+    PayerSession(Address, u32),
+    PayeeSessionCount(Address),
+    PayeeSession(Address, u32),
+    // Tokens accepted as escrow assets
+    AllowedToken(Address),
+    // Admin-configured (min, max) escrow amount per session
+    AmountLimits,
+    // Ledgers added to a session's dispute deadline by its parties
+    DisputeExtension(BytesN<32>),
+    // Platform-wide session counters and per-asset escrow volume
+    Stats,
+    TokenVolume(Address),
+    // Address allowed to act on a party's behalf
+    Operator(Address),
+    // Payout details recorded when a session's escrow is released
+    CompletionReceipt(BytesN<32>),
+    // Admin-set platform fee for a payee's sessions (discount or waiver)
+    PayeeFeeOverride(Address),
+    // Sessions written by `put_session`; they hold no escrowed funds
+    ImportedSession(BytesN<32>),
 }
 
 #[contracttype]
@@ -112,6 +152,31 @@ pub struct MentorReputation {
     pub updated_at: u64,
 }
 
+/// Platform-wide session counters returned by `get_stats`.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SessionStats {
+    pub total_sessions: u64,
+    pub total_completed: u64,
+    pub total_cancelled: u64,
+}
+
+/// Payout details of an approved session, returned by `get_completion_receipt`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompletionReceipt {
+    pub session_id: Bytes,
+    pub payee: Address,
+    pub asset: Address,
+    pub paid_amount: i128,
+    pub fee: i128,
+    pub treasury: Address,
+    pub completed_at: u64,
+    /// Transfers that made up `paid_amount`: the seller alone, or one leg
+    /// per entry of the session's payout splits.
+    pub legs: Vec<PayoutLeg>,
+}
+
 /// Pending upgrade information for 2-phase commit upgrade pattern
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -127,31 +192,229 @@ pub struct PendingUpgrade {
     pub proposed_at_ledger: u32,
 }
 
-#[contracttype]
-#[derive(Clone)]
-pub struct Session {
-    pub version: u32,
-    pub session_id: Bytes,
-    pub payer: Address,
-    pub payee: Address,
-    pub asset: Address,
-    pub amount: i128,
-    pub fee_bps: u32,
-    pub status: SessionStatus,
-    pub created_at: u64,
-    pub updated_at: u64,
-    pub dispute_deadline: u64,
-    pub expires_at: u64, // Timestamp when escrow can be auto-refunded
-    pub payer_approved: bool,
-    pub payee_approved: bool,
-    pub approved_at: u64,
-    pub dispute_opened_at: u64,
-    // Resolution fields for dispute resolution
-    pub resolved_at: u64,
-    pub resolver: Option<Address>,
-    pub resolution_note: Option<Bytes>,
-    pub deadline: u64,
-    pub pending_extension: Option<PendingExtension>,
+map_contracttype! {
+    session_spec [
+        amount,
+        approved_at,
+        asset,
+        created_at,
+        deadline,
+        dispute_deadline,
+        dispute_opened_at,
+        expires_at,
+        fee_bps,
+        metadata_hash,
+        payee,
+        payee_approved,
+        payer,
+        payer_approved,
+        payout_splits,
+        pending_extension,
+        resolution_note,
+        resolved_at,
+        resolver,
+        session_id,
+        status,
+        updated_at,
+        version,
+    ]
+    #[derive(Clone)]
+    pub struct Session {
+        pub version: u32,
+        pub session_id: Bytes,
+        pub payer: Address,
+        pub payee: Address,
+        pub asset: Address,
+        pub amount: i128,
+        pub fee_bps: u32,
+        pub status: SessionStatus,
+        pub created_at: u64,
+        pub updated_at: u64,
+        pub dispute_deadline: u64,
+        pub expires_at: u64, // Timestamp when escrow can be auto-refunded
+        pub payer_approved: bool,
+        pub payee_approved: bool,
+        pub approved_at: u64,
+        pub dispute_opened_at: u64,
+        // Resolution fields for dispute resolution
+        pub resolved_at: u64,
+        pub resolver: Option<Address>,
+        pub resolution_note: Option<Bytes>,
+        pub deadline: u64,
+        pub pending_extension: Option<PendingExtension>,
+        // Version 2: hash of the off-chain booking details (agenda, terms, links)
+        pub metadata_hash: Option<BytesN<32>>,
+        // Version 3: co-mentor payout table; empty when the seller is paid alone
+        pub payout_splits: Vec<PayoutSplit>,
+    }
+}
+
+map_contracttype! {
+    session_v2_spec [
+        amount,
+        approved_at,
+        asset,
+        created_at,
+        deadline,
+        dispute_deadline,
+        dispute_opened_at,
+        expires_at,
+        fee_bps,
+        metadata_hash,
+        payee,
+        payee_approved,
+        payer,
+        payer_approved,
+        pending_extension,
+        resolution_note,
+        resolved_at,
+        resolver,
+        session_id,
+        status,
+        updated_at,
+        version,
+    ]
+    /// Session layout for version 2 records, stored before `payout_splits`
+    /// was added. Only used to decode existing entries; see `load_session`.
+    #[derive(Clone)]
+    pub struct SessionV2 {
+        pub version: u32,
+        pub session_id: Bytes,
+        pub payer: Address,
+        pub payee: Address,
+        pub asset: Address,
+        pub amount: i128,
+        pub fee_bps: u32,
+        pub status: SessionStatus,
+        pub created_at: u64,
+        pub updated_at: u64,
+        pub dispute_deadline: u64,
+        pub expires_at: u64,
+        pub payer_approved: bool,
+        pub payee_approved: bool,
+        pub approved_at: u64,
+        pub dispute_opened_at: u64,
+        pub resolved_at: u64,
+        pub resolver: Option<Address>,
+        pub resolution_note: Option<Bytes>,
+        pub deadline: u64,
+        pub pending_extension: Option<PendingExtension>,
+        pub metadata_hash: Option<BytesN<32>>,
+    }
+}
+
+map_contracttype! {
+    session_v1_spec [
+        amount,
+        approved_at,
+        asset,
+        created_at,
+        deadline,
+        dispute_deadline,
+        dispute_opened_at,
+        expires_at,
+        fee_bps,
+        payee,
+        payee_approved,
+        payer,
+        payer_approved,
+        pending_extension,
+        resolution_note,
+        resolved_at,
+        resolver,
+        session_id,
+        status,
+        updated_at,
+        version,
+    ]
+    /// Session layout for version 1 records, stored before `metadata_hash`
+    /// was added. Only used to decode existing entries; see `load_session`.
+    #[derive(Clone)]
+    pub struct SessionV1 {
+        pub version: u32,
+        pub session_id: Bytes,
+        pub payer: Address,
+        pub payee: Address,
+        pub asset: Address,
+        pub amount: i128,
+        pub fee_bps: u32,
+        pub status: SessionStatus,
+        pub created_at: u64,
+        pub updated_at: u64,
+        pub dispute_deadline: u64,
+        pub expires_at: u64,
+        pub payer_approved: bool,
+        pub payee_approved: bool,
+        pub approved_at: u64,
+        pub dispute_opened_at: u64,
+        pub resolved_at: u64,
+        pub resolver: Option<Address>,
+        pub resolution_note: Option<Bytes>,
+        pub deadline: u64,
+        pub pending_extension: Option<PendingExtension>,
+    }
+}
+
+impl From<SessionV1> for Session {
+    fn from(v1: SessionV1) -> Self {
+        let payout_splits = Vec::new(v1.session_id.env());
+        Session {
+            version: v1.version,
+            session_id: v1.session_id,
+            payer: v1.payer,
+            payee: v1.payee,
+            asset: v1.asset,
+            amount: v1.amount,
+            fee_bps: v1.fee_bps,
+            status: v1.status,
+            created_at: v1.created_at,
+            updated_at: v1.updated_at,
+            dispute_deadline: v1.dispute_deadline,
+            expires_at: v1.expires_at,
+            payer_approved: v1.payer_approved,
+            payee_approved: v1.payee_approved,
+            approved_at: v1.approved_at,
+            dispute_opened_at: v1.dispute_opened_at,
+            resolved_at: v1.resolved_at,
+            resolver: v1.resolver,
+            resolution_note: v1.resolution_note,
+            deadline: v1.deadline,
+            pending_extension: v1.pending_extension,
+            metadata_hash: None,
+            payout_splits,
+        }
+    }
+}
+
+impl From<SessionV2> for Session {
+    fn from(v2: SessionV2) -> Self {
+        let payout_splits = Vec::new(v2.session_id.env());
+        Session {
+            version: v2.version,
+            session_id: v2.session_id,
+            payer: v2.payer,
+            payee: v2.payee,
+            asset: v2.asset,
+            amount: v2.amount,
+            fee_bps: v2.fee_bps,
+            status: v2.status,
+            created_at: v2.created_at,
+            updated_at: v2.updated_at,
+            dispute_deadline: v2.dispute_deadline,
+            expires_at: v2.expires_at,
+            payer_approved: v2.payer_approved,
+            payee_approved: v2.payee_approved,
+            approved_at: v2.approved_at,
+            dispute_opened_at: v2.dispute_opened_at,
+            resolved_at: v2.resolved_at,
+            resolver: v2.resolver,
+            resolution_note: v2.resolution_note,
+            deadline: v2.deadline,
+            pending_extension: v2.pending_extension,
+            metadata_hash: v2.metadata_hash,
+            payout_splits,
+        }
+    }
 }
 
 #[contracttype]
@@ -174,13 +437,30 @@ pub struct ExtensionProposedEvent {
 
 #[contracttype]
 #[derive(Clone, Debug)]
-pub struct ExtensionAcceptedEvent {
+pub struct DeadlineExtendedEvent {
     pub session_id: Bytes,
-    pub accepter: Address,
-    pub new_deadline: u64,
-    pub accepted_at_ledger: u32,
-    // Referrer for fee sharing
-    pub referrer: Option<Address>,
+    pub extended_by: Address,
+    pub additional_ledgers: u64,
+    pub new_dispute_deadline: u64,
+}
+
+map_contracttype! {
+    extension_accepted_event_spec [
+        accepted_at_ledger,
+        accepter,
+        new_deadline,
+        referrer,
+        session_id,
+    ]
+    #[derive(Clone, Debug)]
+    pub struct ExtensionAcceptedEvent {
+        pub session_id: Bytes,
+        pub accepter: Address,
+        pub new_deadline: u64,
+        pub accepted_at_ledger: u32,
+        // Referrer for fee sharing
+        pub referrer: Option<Address>,
+    }
 }
 
 // ── Event structs ────────────────────────────────────────────────────────────
@@ -196,6 +476,51 @@ pub struct SessionRefundedEvent {
     pub timestamp: u64,
 }
 
+/// Emitted when a party commits a new metadata hash to a session.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SessionMetadataUpdatedEvent {
+    pub session_id: Bytes,
+    pub updated_by: Address,
+    pub metadata_hash: BytesN<32>,
+}
+
+/// Emitted when a party registers an operator.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct OperatorSetEvent {
+    pub party: Address,
+    pub operator: Address,
+}
+
+/// Emitted when a party removes its operator.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct OperatorRevokedEvent {
+    pub party: Address,
+    pub operator: Address,
+}
+
+/// Emitted when a stored session is rewritten in the current layout.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SessionMigratedEvent {
+    pub session_id: Bytes,
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+/// Emitted when a session is cancelled and its escrow returned to the buyer.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SessionCancelledEvent {
+    pub session_id: Bytes,
+    pub cancelled_by: Address,
+    pub buyer: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
 /// Emitted when a session is auto-refunded after the dispute window expires.
 /// Closes issue #148.
 #[contracttype]
@@ -229,7 +554,7 @@ pub struct PlatformFeeUpdatedEvent {
     pub updated_by: Address,
 }
 
-/// Emitted when the contract is paused by admin.
+/// Emitted when the contract is paused by the admin or a pauser.
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct PausedEvent {
@@ -237,7 +562,7 @@ pub struct PausedEvent {
     pub timestamp: u64,
 }
 
-/// Emitted when the contract is unpaused by admin.
+/// Emitted when the contract is unpaused by the admin or a pauser.
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct UnpausedEvent {
@@ -277,6 +602,32 @@ pub struct MilestoneReleased {
     pub amount: i128,
 }
 
+/// One co-mentor's share of a session payout, in basis points.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutSplit {
+    pub payee: Address,
+    pub bps: u32,
+}
+
+/// One transfer of a session payout, as recorded on its completion receipt.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutLeg {
+    pub payee: Address,
+    pub amount: i128,
+}
+
+/// Emitted for each leg of a seller payout, including a payout to the
+/// seller alone.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PayoutLegPaid {
+    pub session_id: Bytes,
+    pub payee: Address,
+    pub amount: i128,
+}
+
 // ── Issue #211: Rating structs ───────────────────────────────────────────────
 
 /// Stored per-user rating aggregate.
@@ -300,6 +651,8 @@ pub struct RatingSubmitted {
 // ────────────────────────────────────────────────────────────────────────────
 
 const VERSION: u32 = 1;
+/// Current layout version of stored `Session` records.
+pub const SESSION_VERSION: u32 = 3;
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -352,6 +705,9 @@ pub enum Error {
     MilestoneIndexOutOfBounds = 45,
     AlreadyRated = 46,             // Issue #211: Rating errors
     SessionNotApproved = 47,
+    AssetNotAllowed = 49,          // Asset is not on the token allowlist
+    AmountBelowMinimum = 50,       // Amount is below the configured session minimum
+    AmountAboveMaximum = 51,       // Amount is above the configured session maximum
 }
 
 #[contractimpl]
@@ -384,13 +740,14 @@ impl SkillSyncContract {
 
         env.events().publish(
             (Symbol::new(&env, "Initialized"),),
-            (
+            Initialized {
+                version: EVENT_VERSION,
                 admin,
                 platform_fee_bps,
-                treasury_address,
+                treasury: treasury_address,
                 dispute_window_ledgers,
-                VERSION,
-            ),
+                contract_version: VERSION,
+            },
         );
 
         Ok(())
@@ -434,13 +791,74 @@ impl SkillSyncContract {
             .unwrap_or(0)
     }
 
-    /// Update the treasury wallet. Only callable by admin.
-    /// Emits TreasuryUpdated event (closes issue #152).
-    pub fn set_treasury(env: Env, new_treasury: Address) -> Result<(), Error> {
+    /// Charge `fee_bps` instead of the platform fee on new sessions paid to
+    /// `payee`; 0 waives the fee. Only callable by admin.
+    /// Emits FeeOverrideUpdated.
+    pub fn set_fee_override(env: Env, payee: Address, fee_bps: u32) -> Result<(), Error> {
         let admin = read_admin(&env)?;
         admin.require_auth();
         Self::require_not_paused(&env)?;
 
+        validate_platform_fee_bps(fee_bps)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::PayeeFeeOverride(payee.clone()), &fee_bps);
+
+        env.events().publish(
+            (Symbol::new(&env, "FeeOverrideUpdated"),),
+            FeeOverrideUpdated {
+                payee,
+                fee_bps: Some(fee_bps),
+                updated_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Return `payee` to the platform fee. Sessions already locked keep the
+    /// fee they were created with. Only callable by admin.
+    pub fn clear_fee_override(env: Env, payee: Address) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        Self::require_not_paused(&env)?;
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PayeeFeeOverride(payee.clone()));
+
+        env.events().publish(
+            (Symbol::new(&env, "FeeOverrideUpdated"),),
+            FeeOverrideUpdated {
+                payee,
+                fee_bps: None,
+                updated_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_fee_override(env: Env, payee: Address) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PayeeFeeOverride(payee))
+    }
+
+    /// Fee in bps charged on new sessions paid to `payee`: their override
+    /// if one is set, otherwise the platform fee.
+    pub fn get_payee_fee(env: Env, payee: Address) -> u32 {
+        Self::get_fee_override(env.clone(), payee)
+            .unwrap_or_else(|| Self::get_platform_fee(env))
+    }
+
+    /// Update the treasury wallet. Callable by the admin or a TREASURER_ROLE
+    /// holder. Emits TreasuryUpdated event (closes issue #152).
+    pub fn set_treasury(env: Env, caller: Address, new_treasury: Address) -> Result<(), Error> {
+        require_role(&env, rbac::roles::treasurer(&env), &caller)?;
+        Self::require_not_paused(&env)?;
+
         let old_treasury: Address = env
             .storage()
             .instance()
@@ -456,6 +874,29 @@ impl SkillSyncContract {
             TreasuryUpdated {
                 old_treasury,
                 new_treasury,
+                updated_by: caller,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Allow `asset` to be used as an escrow token. Only callable by admin.
+    /// Emits TokenAllowlistUpdated.
+    pub fn add_allowed_token(env: Env, asset: Address) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        Self::require_not_paused(&env)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::AllowedToken(asset.clone()), &true);
+
+        env.events().publish(
+            (Symbol::new(&env, "TokenAllowlistUpdated"),),
+            TokenAllowlistUpdated {
+                asset,
+                allowed: true,
                 updated_by: admin,
             },
         );
@@ -463,9 +904,84 @@ impl SkillSyncContract {
         Ok(())
     }
 
-    pub fn pause(env: Env) -> Result<(), Error> {
+    /// Remove `asset` from the allowlist. Existing sessions in that asset
+    /// are unaffected; only new escrows are rejected. Only callable by admin.
+    /// Emits TokenAllowlistUpdated.
+    pub fn remove_allowed_token(env: Env, asset: Address) -> Result<(), Error> {
         let admin = read_admin(&env)?;
         admin.require_auth();
+        Self::require_not_paused(&env)?;
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::AllowedToken(asset.clone()));
+
+        env.events().publish(
+            (Symbol::new(&env, "TokenAllowlistUpdated"),),
+            TokenAllowlistUpdated {
+                asset,
+                allowed: false,
+                updated_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn is_token_allowed(env: Env, asset: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AllowedToken(asset))
+            .unwrap_or(false)
+    }
+
+    /// Set the minimum and maximum amount a single session may escrow.
+    /// Requires `0 < min <= max <= MAX_AMOUNT`. Only callable by admin.
+    /// Emits LimitsUpdated.
+    pub fn set_amount_limits(env: Env, min_amount: i128, max_amount: i128) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        Self::require_not_paused(&env)?;
+
+        if min_amount <= 0 || min_amount > max_amount || max_amount > MAX_AMOUNT {
+            return Err(Error::InvalidAmount);
+        }
+
+        let (old_min, old_max) = Self::get_amount_limits(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::AmountLimits, &(min_amount, max_amount));
+
+        env.events().publish(
+            (Symbol::new(&env, "LimitsUpdated"),),
+            LimitsUpdated {
+                old_min,
+                old_max,
+                new_min: min_amount,
+                new_max: max_amount,
+                updated_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Returns the `(min, max)` session amount limits.
+    /// Defaults to `(1, MAX_AMOUNT)` when not configured.
+    pub fn get_amount_limits(env: Env) -> (i128, i128) {
+        env.storage()
+            .instance()
+            .get(&DataKey::AmountLimits)
+            .unwrap_or((1, MAX_AMOUNT))
+    }
+
+    /// Emergency stop. Callable by the admin or a PAUSER_ROLE holder.
+    /// While paused, new escrows, completions, approvals and admin
+    /// configuration changes fail with ContractPaused; reads and refund
+    /// paths (cancel_session, auto_refund, cancel_expired_session) still work.
+    /// Emits PausedEvent.
+    pub fn pause(env: Env, caller: Address) -> Result<(), Error> {
+        require_role(&env, rbac::roles::pauser(&env), &caller)?;
 
         if Self::is_paused(env.clone()) {
             return Ok(());
@@ -475,7 +991,7 @@ impl SkillSyncContract {
         env.events().publish(
             (Symbol::new(&env, "Paused"),),
             PausedEvent {
-                admin,
+                admin: caller,
                 timestamp: env.ledger().timestamp(),
             },
         );
@@ -483,9 +999,10 @@ impl SkillSyncContract {
         Ok(())
     }
 
-    pub fn unpause(env: Env) -> Result<(), Error> {
-        let admin = read_admin(&env)?;
-        admin.require_auth();
+    /// Lift the emergency stop. Callable by the admin or a PAUSER_ROLE holder.
+    /// Emits UnpausedEvent.
+    pub fn unpause(env: Env, caller: Address) -> Result<(), Error> {
+        require_role(&env, rbac::roles::pauser(&env), &caller)?;
 
         if !Self::is_paused(env.clone()) {
             return Ok(());
@@ -495,7 +1012,7 @@ impl SkillSyncContract {
         env.events().publish(
             (Symbol::new(&env, "Unpaused"),),
             UnpausedEvent {
-                admin,
+                admin: caller,
                 timestamp: env.ledger().timestamp(),
             },
         );
@@ -522,6 +1039,179 @@ impl SkillSyncContract {
         Ok(())
     }
 
+    /// Grant `role` (see `rbac::roles`) to `account`. Only callable by admin;
+    /// stays available while the contract is paused. Emits RoleGranted.
+    pub fn grant_role(env: Env, role: Bytes, account: Address) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        rbac::grant_role(&env, role.clone(), account.clone());
+
+        env.events().publish(
+            (Symbol::new(&env, "RoleGranted"),),
+            RoleGranted {
+                role,
+                account,
+                granted_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Revoke `role` from `account`. Only callable by admin.
+    /// Emits RoleRevoked.
+    pub fn revoke_role(env: Env, role: Bytes, account: Address) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        rbac::revoke_role(&env, role.clone(), account.clone());
+
+        env.events().publish(
+            (Symbol::new(&env, "RoleRevoked"),),
+            RoleRevoked {
+                role,
+                account,
+                revoked_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Whether `account` was granted `role`. The admin is not reported here
+    /// but passes every role check.
+    pub fn has_role(env: Env, role: Bytes, account: Address) -> bool {
+        rbac::has_role(&env, role, account)
+    }
+
+    /// Set the delay between `propose_upgrade` and `upgrade`. It must be at
+    /// least MIN_UPGRADE_TIMELOCK_SECONDS and can only be raised once set, so
+    /// a pending upgrade's waiting period cannot be shortened. A pending
+    /// proposal's deadline is moved to `proposed_at + timelock_seconds`, so
+    /// one made before the raise also waits out the new timelock.
+    pub fn set_upgrade_timelock(env: Env, timelock_seconds: u64) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        if timelock_seconds < MIN_UPGRADE_TIMELOCK_SECONDS
+            || timelock_seconds < Self::get_upgrade_timelock(env.clone())
+        {
+            return Err(Error::InvalidTimelock);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::UpgradeTimelock, &timelock_seconds);
+
+        if let Some(mut pending) = Self::get_pending_upgrade(env.clone()) {
+            pending.deadline = pending.proposed_at.saturating_add(timelock_seconds);
+            env.storage()
+                .instance()
+                .set(&DataKey::PendingUpgrade, &pending);
+        }
+        Ok(())
+    }
+
+    /// The configured upgrade timelock, or 0 while none has been set.
+    pub fn get_upgrade_timelock(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::UpgradeTimelock)
+            .unwrap_or(0)
+    }
+
+    /// Announce the WASM hash the admin intends to install. Replaces any
+    /// pending proposal. Required before `upgrade` when a timelock is set.
+    pub fn propose_upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<PendingUpgrade, Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        let now = env.ledger().timestamp();
+        let timelock = Self::get_upgrade_timelock(env.clone());
+        let pending = PendingUpgrade {
+            new_wasm_hash: new_wasm_hash.into(),
+            proposed_at: now,
+            deadline: if timelock == 0 { 0 } else { now + timelock },
+            proposed_at_ledger: env.ledger().sequence(),
+        };
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingUpgrade, &pending);
+        env.events()
+            .publish((Symbol::new(&env, "UpgradeProposed"),), pending.clone());
+
+        Ok(pending)
+    }
+
+    pub fn cancel_upgrade(env: Env) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        let pending = Self::get_pending_upgrade(env.clone()).ok_or(Error::UpgradeNotProposed)?;
+        env.storage().instance().remove(&DataKey::PendingUpgrade);
+        env.events()
+            .publish((Symbol::new(&env, "UpgradeCancelled"),), pending);
+
+        Ok(())
+    }
+
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        env.storage().instance().get(&DataKey::PendingUpgrade)
+    }
+
+    /// Replace the contract code with `new_wasm_hash`, keeping all storage.
+    /// When an upgrade timelock is set the hash must have been proposed and
+    /// the timelock must have elapsed; a pending proposal always has to
+    /// match. Emits ContractUpgraded.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        let new_hash: Bytes = new_wasm_hash.clone().into();
+        match Self::get_pending_upgrade(env.clone()) {
+            Some(pending) => {
+                if pending.new_wasm_hash != new_hash {
+                    return Err(Error::UpgradeNotProposed);
+                }
+                if pending.deadline != 0 && env.ledger().timestamp() < pending.deadline {
+                    return Err(Error::UpgradeNotReady);
+                }
+            }
+            None => {
+                if Self::get_upgrade_timelock(env.clone()) != 0 {
+                    return Err(Error::UpgradeNotProposed);
+                }
+            }
+        }
+
+        let old_wasm_hash: Bytes = env
+            .storage()
+            .instance()
+            .get(&DataKey::CurrentWasmHash)
+            .unwrap_or(Bytes::new(&env));
+
+        env.storage().instance().remove(&DataKey::PendingUpgrade);
+        env.storage()
+            .instance()
+            .set(&DataKey::CurrentWasmHash, &new_hash);
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+
+        env.events().publish(
+            (Symbol::new(&env, "ContractUpgraded"),),
+            ContractUpgraded {
+                old_wasm_hash,
+                new_wasm_hash: new_hash,
+                upgraded_by: admin,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(())
+    }
+
     pub fn create_session(
         env: Env,
         payer: Address,
@@ -530,12 +1220,11 @@ impl SkillSyncContract {
         amount: i128,
     ) -> Result<Bytes, Error> {
         Self::require_not_paused(&env)?;
-        payer.require_auth();
 
-        let fee_bps = Self::get_platform_fee(env.clone());
         let session_id = Self::generate_session_id(&env);
 
-        // Lock funds, create the session record, and return the generated ID.
+        // Lock funds at the platform fee, create the session record, and
+        // return the generated ID.
         Self::lock_funds(
             env,
             session_id.clone(),
@@ -543,28 +1232,103 @@ impl SkillSyncContract {
             payee,
             asset,
             amount,
-            fee_bps,
+            None,
         )?;
 
         Ok(session_id)
     }
 
+    /// Store a session record as given. Admin-only. No funds are escrowed
+    /// for it, so `cancel_session` will not refund it.
     pub fn put_session(env: Env, session: Session) -> Result<(), Error> {
         Self::require_not_paused(&env)?;
-        let key = DataKey::Session(session.session_id.clone());
-        if env.storage().persistent().has(&key) {
-            return Err(Error::DuplicateSessionId);
-        }
-        env.storage().persistent().set(&key, &session);
+        read_admin(&env)?.require_auth();
+        insert_session(&env, &session)?;
+        env.storage().persistent().set(
+            &DataKey::ImportedSession(env.crypto().sha256(&session.session_id)),
+            &true,
+        );
         Ok(())
     }
 
     pub fn get_session(env: Env, session_id: Bytes) -> Option<Session> {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Session(session_id))
+        load_session(&env, &session_id)
+    }
+
+    /// The 32-byte storage key a session id maps to, so clients can build
+    /// ledger keys for reads and footprints without calling the contract.
+    pub fn derive_session_key(env: Env, session_id: Bytes) -> BytesN<32> {
+        env.crypto().sha256(&session_id)
     }
 
+    /// Commit a hash of the off-chain booking details to a session.
+    /// Callable by the buyer or seller until the session is approved.
+    /// Emits SessionMetadataUpdatedEvent.
+    pub fn set_session_metadata(
+        env: Env,
+        session_id: Bytes,
+        caller: Address,
+        metadata_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let mut session =
+            Self::get_session(env.clone(), session_id.clone()).ok_or(Error::SessionNotFound)?;
+
+        if caller != session.payer && caller != session.payee {
+            return Err(Error::NotAuthorizedParty);
+        }
+
+        if session.status != SessionStatus::Locked && session.status != SessionStatus::Completed {
+            return Err(Error::InvalidSessionStatus);
+        }
+
+        session.metadata_hash = Some(metadata_hash.clone());
+        session.updated_at = env.ledger().timestamp();
+
+        save_session(&env, &session);
+
+        env.events().publish(
+            (Symbol::new(&env, "SessionMetadataUpdated"),),
+            SessionMetadataUpdatedEvent {
+                session_id,
+                updated_by: caller,
+                metadata_hash,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Rewrite a session stored in an older layout at SESSION_VERSION.
+    /// Admin only; allowed while paused so storage can be migrated during
+    /// maintenance. Returns false if the session is already current.
+    pub fn migrate_session(env: Env, session_id: Bytes) -> Result<bool, Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        migrate_stored_session(&env, session_id)
+    }
+
+    /// Migrate several sessions in one call, skipping those already at
+    /// SESSION_VERSION. Fails with SessionNotFound if any id is unknown.
+    /// Returns the number of sessions rewritten.
+    pub fn migrate_sessions_batch(env: Env, session_ids: Vec<Bytes>) -> Result<u32, Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        let mut migrated: u32 = 0;
+        for session_id in session_ids.iter() {
+            if migrate_stored_session(&env, session_id)? {
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// Escrow `amount` plus the platform fee from `payer` for a new session.
+    /// `fee_bps` overrides the fee for this session (bounded by
+    /// PLATFORM_FEE_MAX_BPS) and requires admin authorization; when `None`
+    /// the payee's fee override or the stored platform fee is used.
     pub fn lock_funds(
         env: Env,
         session_id: Bytes,
@@ -572,21 +1336,31 @@ impl SkillSyncContract {
         payee: Address,
         asset: Address,
         amount: i128,
-        _fee_bps: u32,
+        fee_bps: Option<u32>,
     ) -> Result<(), Error> {
         Self::require_not_paused(&env)?;
+        payer.require_auth();
         acquire_lock(&env)?;
 
         validate_session_id(&session_id)?;
         validate_amount(amount)?;
+        validate_amount_limits(&env, amount)?;
         validate_different_addresses(&payer, &payee)?;
+        validate_asset(&env, &asset)?;
 
         let now = env.ledger().timestamp();
         let dispute_window_ledgers = Self::get_dispute_window(env.clone());
         let current_ledger = env.ledger().sequence();
         let dispute_deadline = (current_ledger + dispute_window_ledgers) as u64;
         let expires_at = now + ESCROW_DURATION_SECONDS;
-        let fee_bps = Self::get_platform_fee(env.clone());
+        let fee_bps = match fee_bps {
+            Some(bps) => {
+                read_admin(&env)?.require_auth();
+                validate_platform_fee_bps(bps)?;
+                bps
+            }
+            None => Self::get_payee_fee(env.clone(), payee.clone()),
+        };
 
         let fee = amount
             .checked_mul(fee_bps as i128)
@@ -603,7 +1377,7 @@ impl SkillSyncContract {
         }
 
         let session = Session {
-            version: VERSION,
+            version: SESSION_VERSION,
             session_id: session_id.clone(),
             payer: payer.clone(),
             payee: payee.clone(),
@@ -624,9 +1398,13 @@ impl SkillSyncContract {
             resolver: None,
             resolution_note: None,
             pending_extension: None,
+            metadata_hash: None,
+            payout_splits: Vec::new(&env),
         };
 
-        Self::put_session(env.clone(), session)?;
+        insert_session(&env, &session)?;
+        Self::add_to_party_index(&env, &session);
+        Self::record_session_locked(&env, &session);
         Self::add_to_expiry_index(env.clone(), session_id.clone(), expires_at)?;
 
         let contract_id = env.current_contract_address();
@@ -634,19 +1412,48 @@ impl SkillSyncContract {
 
         env.events().publish(
             (Symbol::new(&env, "FundsLocked"),),
-            (session_id, payer, payee, amount, fee),
+            FundsLocked {
+                version: EVENT_VERSION,
+                session_id,
+                payer,
+                payee,
+                asset,
+                amount,
+                fee,
+            },
         );
 
         release_lock(&env);
         Ok(())
     }
 
+    /// `lock_funds` for native XLM, escrowed through its Stellar Asset
+    /// Contract. The native asset must be on the token allowlist like any
+    /// other asset; see `get_native_asset`. An explicit `fee_bps` requires
+    /// admin authorization, as in `lock_funds`.
+    pub fn lock_funds_native(
+        env: Env,
+        session_id: Bytes,
+        payer: Address,
+        payee: Address,
+        amount: i128,
+        fee_bps: Option<u32>,
+    ) -> Result<(), Error> {
+        let asset = native_asset_address(&env);
+        Self::lock_funds(env, session_id, payer, payee, asset, amount, fee_bps)
+    }
+
+    /// Address of the Stellar Asset Contract for native XLM.
+    pub fn get_native_asset(env: Env) -> Address {
+        native_asset_address(&env)
+    }
+
     pub fn complete_session(
         env: Env,
         session_id: Bytes,
         caller: Address,
         nonce: u64,
-    ) -> Result<(), Error> {
+    ) -> Result<(), soroban_sdk::Error> {
         Self::require_not_paused(&env)?;
         use_nonce(&env, &caller, nonce)?;
         caller.require_auth();
@@ -654,13 +1461,16 @@ impl SkillSyncContract {
         let mut session =
             Self::get_session(env.clone(), session_id.clone()).ok_or(Error::SessionNotFound)?;
 
+        if session.status == SessionStatus::Completed || session.status == SessionStatus::Approved {
+            return Err(SessionError::SessionAlreadyCompleted.into());
+        }
         if session.status != SessionStatus::Locked {
-            return Err(Error::InvalidSessionStatus);
+            return Err(Error::InvalidSessionStatus.into());
         }
 
         // Issue #208: cannot complete after expiry
         if env.ledger().sequence() as u64 > session.deadline {
-            return Err(Error::SessionExpired);
+            return Err(Error::SessionExpired.into());
         }
 
         let now = env.ledger().timestamp();
@@ -668,22 +1478,31 @@ impl SkillSyncContract {
         session.status = SessionStatus::Completed;
         session.updated_at = now;
 
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
+        save_session(&env, &session);
+
+        let mut stats = read_stats(&env);
+        stats.total_completed = stats.total_completed.saturating_add(1);
+        write_stats(&env, &stats);
 
         env.events().publish(
             (Symbol::new(&env, "SessionCompleted"),),
-            (session_id, session.payee.clone(), session.amount),
+            SessionCompleted {
+                version: EVENT_VERSION,
+                session_id,
+                payee: session.payee.clone(),
+                amount: session.amount,
+                timestamp: now,
+            },
         );
 
         Ok(())
     }
 
     /// Auto-refund a session after the dispute window has elapsed.
+    /// Remains available while the contract is paused.
     /// Emits AutoRefundExecutedEvent (closes issue #148) and
     /// SessionRefundedEvent (closes issue #147).
     pub fn auto_refund(env: Env, session_id: Bytes) -> Result<(), Error> {
-        Self::require_not_paused(&env)?;
         let mut session =
             Self::get_session(env.clone(), session_id.clone()).ok_or(Error::SessionNotFound)?;
 
@@ -714,17 +1533,16 @@ impl SkillSyncContract {
             .checked_add(fee)
             .ok_or(Error::FeeCalculationOverflow)?;
 
-        token_client.transfer(&contract_id, &session.payer, &total_locked);
-
         let completed_at = session.updated_at;
         session.status = SessionStatus::Refunded;
         session.updated_at = now;
 
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
+        save_session(&env, &session);
 
         Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
 
+        token_client.transfer(&contract_id, &session.payer, &total_locked);
+
         // Emit AutoRefundExecuted event (issue #148)
         env.events().publish(
             (Symbol::new(&env, "AutoRefundExecuted"),),
@@ -751,14 +1569,102 @@ impl SkillSyncContract {
         Ok(())
     }
 
-    /// Open a dispute on a session.
-    /// Emits DisputeOpenedEvent (closes issue #149).
+    /// Cancel a session and return the escrowed amount plus fee to the buyer.
+    /// The buyer may cancel only while the session is Locked; once the seller
+    /// has completed it, a refund goes through the dispute flow. The admin may
+    /// cancel a Disputed session. Sessions stored with `put_session` hold no
+    /// escrow and fail with RefundFailed.
+    /// Remains available while the contract is paused.
+    /// Emits SessionCancelledEvent.
+    pub fn cancel_session(env: Env, session_id: Bytes, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+        acquire_lock(&env)?;
+
+        let mut session =
+            Self::get_session(env.clone(), session_id.clone()).ok_or(Error::SessionNotFound)?;
+
+        if is_imported_session(&env, &session_id) {
+            release_lock(&env);
+            return Err(Error::RefundFailed);
+        }
+
+        match session.status {
+            SessionStatus::Locked => {
+                if caller != session.payer {
+                    release_lock(&env);
+                    return Err(Error::NotAuthorizedParty);
+                }
+            }
+            SessionStatus::Disputed => {
+                if caller != read_admin(&env)? {
+                    release_lock(&env);
+                    return Err(Error::Unauthorized);
+                }
+            }
+            _ => {
+                release_lock(&env);
+                return Err(Error::InvalidSessionStatus);
+            }
+        }
+
+        let fee = session
+            .amount
+            .checked_mul(session.fee_bps as i128)
+            .ok_or(Error::FeeCalculationOverflow)?
+            .checked_div(10000)
+            .ok_or(Error::FeeCalculationOverflow)?;
+
+        // Milestones already paid out to the seller are no longer in escrow.
+        let released = released_milestone_total(&env, &session_id, session.amount)?;
+        let refund = session
+            .amount
+            .checked_sub(released)
+            .and_then(|remaining| remaining.checked_add(fee))
+            .ok_or(Error::FeeCalculationOverflow)?;
+
+        let now = env.ledger().timestamp();
+        session.status = SessionStatus::Cancelled;
+        session.updated_at = now;
+
+        save_session(&env, &session);
+
+        let mut stats = read_stats(&env);
+        stats.total_cancelled = stats.total_cancelled.saturating_add(1);
+        write_stats(&env, &stats);
+
+        let token_client = token::Client::new(&env, &session.asset);
+        let contract_id = env.current_contract_address();
+        if refund > 0 {
+            token_client.transfer(&contract_id, &session.payer, &refund);
+        }
+
+        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
+
+        env.events().publish(
+            (Symbol::new(&env, "SessionCancelled"),),
+            SessionCancelledEvent {
+                session_id,
+                cancelled_by: caller,
+                buyer: session.payer,
+                amount: refund,
+                timestamp: now,
+            },
+        );
+
+        release_lock(&env);
+        Ok(())
+    }
+
+    /// Open a dispute on a session. Only the buyer or seller may open a
+    /// dispute, and only until the session's `dispute_deadline` ledger.
+    /// Emits DisputeOpenedEvent under the `DisputeRaised` topic (closes
+    /// issue #149), and under the deprecated `DisputeOpened` topic.
     pub fn open_dispute(
         env: Env,
         session_id: Bytes,
         caller: Address,
         reason: Bytes,
-    ) -> Result<(), Error> {
+    ) -> Result<(), soroban_sdk::Error> {
         Self::require_not_paused(&env)?;
         caller.require_auth();
 
@@ -766,11 +1672,15 @@ impl SkillSyncContract {
             Self::get_session(env.clone(), session_id.clone()).ok_or(Error::SessionNotFound)?;
 
         if caller != session.payer && caller != session.payee {
-            return Err(Error::Unauthorized);
+            return Err(Error::Unauthorized.into());
         }
 
         if session.status != SessionStatus::Locked && session.status != SessionStatus::Completed {
-            return Err(Error::InvalidSessionStatus);
+            return Err(Error::InvalidSessionStatus.into());
+        }
+
+        if env.ledger().sequence() as u64 > session.dispute_deadline {
+            return Err(TimeoutDisputeError::DisputeWindowElapsed.into());
         }
 
         let now = env.ledger().timestamp();
@@ -779,21 +1689,93 @@ impl SkillSyncContract {
         session.updated_at = now;
         session.dispute_opened_at = now;
 
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
+        save_session(&env, &session);
+
+        // Emit DisputeRaised event (issue #149). The same payload is still
+        // published under the deprecated DisputeOpened topic so existing
+        // indexers keep working until they move to DisputeRaised.
+        let event = DisputeOpenedEvent {
+            session_id,
+            opened_by: caller,
+            reason,
+            timestamp: now,
+        };
+        env.events().publish((Symbol::new(&env, "DisputeOpened"),), event.clone());
+        env.events().publish((Symbol::new(&env, "DisputeRaised"),), event);
+
+        Ok(())
+    }
+
+    /// Raise a dispute without a free-text reason.
+    /// Restricted to the buyer or seller, and only while the dispute window
+    /// (`dispute_deadline`) is still open.
+    pub fn raise_dispute(env: Env, session_id: Bytes, caller: Address) -> Result<(), soroban_sdk::Error> {
+        let reason = Bytes::new(&env);
+        Self::open_dispute(env, session_id, caller, reason)
+    }
+
+    /// Push a session's dispute deadline back by `additional_ledgers`.
+    /// The buyer or seller may extend while the window is still open, up to
+    /// MAX_DISPUTE_EXTENSION_LEDGERS in total; the admin is not capped.
+    pub fn extend_dispute_deadline(
+        env: Env,
+        session_id: Bytes,
+        caller: Address,
+        additional_ledgers: u64,
+    ) -> Result<u64, soroban_sdk::Error> {
+        Self::require_not_paused(&env)?;
+        caller.require_auth();
+
+        if additional_ledgers == 0 {
+            return Err(Error::InvalidExtensionDuration.into());
+        }
+
+        let mut session =
+            Self::get_session(env.clone(), session_id.clone()).ok_or(Error::SessionNotFound)?;
+
+        if session.status != SessionStatus::Locked && session.status != SessionStatus::Completed {
+            return Err(Error::InvalidSessionStatus.into());
+        }
+
+        let is_admin = caller == read_admin(&env)?;
+        if !is_admin {
+            if caller != session.payer && caller != session.payee {
+                return Err(Error::NotAuthorizedParty.into());
+            }
+            if env.ledger().sequence() as u64 > session.dispute_deadline {
+                return Err(TimeoutDisputeError::DisputeWindowElapsed.into());
+            }
+
+            let extension_key = DataKey::DisputeExtension(env.crypto().sha256(&session_id));
+            let extended: u64 = env.storage().persistent().get(&extension_key).unwrap_or(0);
+            let total = extended
+                .checked_add(additional_ledgers)
+                .ok_or(Error::InvalidExtensionDuration)?;
+            if total > MAX_DISPUTE_EXTENSION_LEDGERS {
+                return Err(Error::InvalidExtensionDuration.into());
+            }
+            env.storage().persistent().set(&extension_key, &total);
+        }
+
+        session.dispute_deadline = session
+            .dispute_deadline
+            .checked_add(additional_ledgers)
+            .ok_or(Error::InvalidExtensionDuration)?;
+        session.updated_at = env.ledger().timestamp();
+
+        save_session(&env, &session);
 
-        // Emit DisputeOpened event (issue #149)
         env.events().publish(
-            (Symbol::new(&env, "DisputeOpened"),),
-            DisputeOpenedEvent {
+            (Symbol::new(&env, "DeadlineExtended"),),
+            DeadlineExtendedEvent {
                 session_id,
-                opened_by: caller,
-                reason,
-                timestamp: now,
+                extended_by: caller,
+                additional_ledgers,
+                new_dispute_deadline: session.dispute_deadline,
             },
         );
 
-        Ok(())
+        Ok(session.dispute_deadline)
     }
 
     pub fn resolve_dispute(
@@ -848,6 +1830,17 @@ impl SkillSyncContract {
             .checked_div(10000)
             .ok_or(Error::FeeCalculationOverflow)?;
 
+        let now = env.ledger().timestamp();
+        session.status = SessionStatus::Resolved;
+        session.updated_at = now;
+        session.resolved_at = now;
+        session.resolver = Some(admin.clone());
+        session.resolution_note = None;
+
+        save_session(&env, &session);
+
+        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
+
         let treasury = Self::get_treasury(env.clone());
         let token_client = token::Client::new(&env, &session.asset);
         let contract_id = env.current_contract_address();
@@ -856,24 +1849,12 @@ impl SkillSyncContract {
             token_client.transfer(&contract_id, &session.payer, &buyer_share);
         }
         if seller_share > 0 {
-            token_client.transfer(&contract_id, &session.payee, &seller_share);
+            Self::pay_payees(&env, &token_client, &session, seller_share)?;
         }
         if fee > 0 {
             token_client.transfer(&contract_id, &treasury, &fee);
         }
 
-        let now = env.ledger().timestamp();
-        session.status = SessionStatus::Resolved;
-        session.updated_at = now;
-        session.resolved_at = now;
-        session.resolver = Some(admin.clone());
-        session.resolution_note = None;
-
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
-
-        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
-
         env.events().publish(
             (Symbol::new(&env, "DisputeResolved"),),
             DisputeResolved {
@@ -926,7 +1907,8 @@ impl SkillSyncContract {
         use_nonce(&env, &session.payer, buyer_nonce)?;
         use_nonce(&env, &session.payee, seller_nonce)?;
 
-        // Calculate fee and payout
+        // The fee was escrowed on top of the amount, so the seller receives
+        // the full amount, less any milestones already released.
         let fee = session
             .amount
             .checked_mul(session.fee_bps as i128)
@@ -935,32 +1917,35 @@ impl SkillSyncContract {
             .ok_or(Error::FeeCalculationOverflow)?;
         let payout = session
             .amount
-            .checked_sub(fee)
+            .checked_sub(released_milestone_total(&env, &session_id, session.amount)?)
             .ok_or(Error::FeeCalculationOverflow)?;
 
+        // Update session before paying out (checks-effects-interactions)
+        let now = env.ledger().timestamp();
+        session.status = SessionStatus::Approved;
+        session.updated_at = now;
+        session.approved_at = now;
+
+        save_session(&env, &session);
+
+        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
+
         // Transfer funds
         let token_client = token::Client::new(&env, &session.asset);
         let contract_id = env.current_contract_address();
         let treasury = Self::get_treasury(env.clone());
 
-        if payout > 0 {
-            token_client.transfer(&contract_id, &session.payee, &payout);
-        }
+        let legs = if payout > 0 {
+            Self::pay_payees(&env, &token_client, &session, payout)?
+        } else {
+            Vec::new(&env)
+        };
+        record_completion_receipt(&env, &session, payout, fee, &treasury, legs);
+
         if fee > 0 {
             token_client.transfer(&contract_id, &treasury, &fee);
         }
 
-        // Update session
-        let now = env.ledger().timestamp();
-        session.status = SessionStatus::Approved;
-        session.updated_at = now;
-        session.approved_at = now;
-
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
-
-        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
-
         // Emit event
         env.events().publish(
             (Symbol::new(&env, "OffchainApprovalExecuted"),),
@@ -977,8 +1962,60 @@ impl SkillSyncContract {
         Ok(())
     }
 
+    /// Let `operator` act for `party` on calls that accept delegation, such
+    /// as `approve_session`. Replaces any existing operator, emitting
+    /// OperatorRevoked for it before OperatorSet.
+    pub fn set_operator(env: Env, party: Address, operator: Address) -> Result<(), Error> {
+        party.require_auth();
+
+        if operator == party {
+            return Err(Error::InvalidAddress);
+        }
+
+        let key = DataKey::Operator(party.clone());
+        if let Some(previous) = env.storage().persistent().get::<_, Address>(&key) {
+            if previous != operator {
+                env.events().publish(
+                    (Symbol::new(&env, "OperatorRevoked"),),
+                    OperatorRevokedEvent {
+                        party: party.clone(),
+                        operator: previous,
+                    },
+                );
+            }
+        }
+
+        env.storage().persistent().set(&key, &operator);
+        env.events().publish(
+            (Symbol::new(&env, "OperatorSet"),),
+            OperatorSetEvent { party, operator },
+        );
+
+        Ok(())
+    }
+
+    pub fn revoke_operator(env: Env, party: Address) -> Result<(), Error> {
+        party.require_auth();
+
+        let key = DataKey::Operator(party.clone());
+        if let Some(operator) = env.storage().persistent().get::<_, Address>(&key) {
+            env.storage().persistent().remove(&key);
+            env.events().publish(
+                (Symbol::new(&env, "OperatorRevoked"),),
+                OperatorRevokedEvent { party, operator },
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn get_operator(env: Env, party: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Operator(party))
+    }
+
     /// Approve a session by the buyer after completion.
     /// This transfers funds to the seller and collects the platform fee.
+    /// Callable by the buyer or the buyer's operator.
     pub fn approve_session(
         env: Env,
         session_id: Bytes,
@@ -1001,11 +2038,14 @@ impl SkillSyncContract {
             return Err(Error::SessionExpired);
         }
 
-        if caller != session.payer {
+        if caller != session.payer
+            && Self::get_operator(env.clone(), session.payer.clone()) != Some(caller.clone())
+        {
             return Err(Error::NotAuthorizedParty);
         }
 
-        // Calculate fee and payout
+        // The fee was escrowed on top of the amount, so the seller receives
+        // the full amount, less any milestones already released.
         let fee = session
             .amount
             .checked_mul(session.fee_bps as i128)
@@ -1014,36 +2054,39 @@ impl SkillSyncContract {
             .ok_or(Error::FeeCalculationOverflow)?;
         let payout = session
             .amount
-            .checked_sub(fee)
+            .checked_sub(released_milestone_total(&env, &session_id, session.amount)?)
             .ok_or(Error::FeeCalculationOverflow)?;
 
+        // Update session before paying out (checks-effects-interactions)
+        let now = env.ledger().timestamp();
+        session.status = SessionStatus::Approved;
+        session.updated_at = now;
+        session.approved_at = now;
+
+        save_session(&env, &session);
+
+        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
+
         // Transfer funds
         let token_client = token::Client::new(&env, &session.asset);
         let contract_id = env.current_contract_address();
         let treasury = Self::get_treasury(env.clone());
 
-        if payout > 0 {
-            token_client.transfer(&contract_id, &session.payee, &payout);
-        }
+        let legs = if payout > 0 {
+            Self::pay_payees(&env, &token_client, &session, payout)?
+        } else {
+            Vec::new(&env)
+        };
+        record_completion_receipt(&env, &session, payout, fee, &treasury, legs);
+
         if fee > 0 {
             token_client.transfer(&contract_id, &treasury, &fee);
         }
 
-        // Update session
-        let now = env.ledger().timestamp();
-        session.status = SessionStatus::Approved;
-        session.updated_at = now;
-        session.approved_at = now;
-
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
-
-        Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
-
-        // Emit event (assuming there's a SessionApprovedEvent, but since it's not defined, I'll use OffchainApprovalExecuted for now)
         env.events().publish(
             (Symbol::new(&env, "SessionApproved"),),
             SessionApprovedEvent {
+                version: EVENT_VERSION,
                 session_id,
                 buyer: session.payer,
                 seller: session.payee,
@@ -1092,8 +2135,7 @@ impl SkillSyncContract {
         });
         session.updated_at = env.ledger().timestamp();
 
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
+        save_session(&env, &session);
 
         env.events().publish(
             (Symbol::new(&env, "ExtensionProposed"),),
@@ -1137,8 +2179,7 @@ impl SkillSyncContract {
         session.pending_extension = None;
         session.updated_at = env.ledger().timestamp();
 
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
+        save_session(&env, &session);
 
         env.events().publish(
             (Symbol::new(&env, "ExtensionAccepted"),),
@@ -1259,6 +2300,212 @@ impl SkillSyncContract {
         Ok(())
     }
 
+    /// Session ids paid for by `payer`, oldest first.
+    /// `page` is zero-based; `limit` is capped at MAX_PAGE_LIMIT.
+    pub fn sessions_by_payer(env: Env, payer: Address, page: u32, limit: u32) -> Vec<Bytes> {
+        let count = read_index_count(&env, &DataKey::PayerSessionCount(payer.clone()));
+        paginate_index(&env, count, page, limit, |i| {
+            DataKey::PayerSession(payer.clone(), i)
+        })
+    }
+
+    /// Session ids payable to `payee`, oldest first.
+    /// `page` is zero-based; `limit` is capped at MAX_PAGE_LIMIT.
+    pub fn sessions_by_payee(env: Env, payee: Address, page: u32, limit: u32) -> Vec<Bytes> {
+        let count = read_index_count(&env, &DataKey::PayeeSessionCount(payee.clone()));
+        paginate_index(&env, count, page, limit, |i| {
+            DataKey::PayeeSession(payee.clone(), i)
+        })
+    }
+
+    /// Append a newly locked session to its payer's and payee's indexes.
+    /// Only called from the authenticated creation paths, never from
+    /// `put_session`.
+    fn add_to_party_index(env: &Env, session: &Session) {
+        let payer = &session.payer;
+        append_to_index(
+            env,
+            &DataKey::PayerSessionCount(payer.clone()),
+            |i| DataKey::PayerSession(payer.clone(), i),
+            &session.session_id,
+        );
+        let payee = &session.payee;
+        append_to_index(
+            env,
+            &DataKey::PayeeSessionCount(payee.clone()),
+            |i| DataKey::PayeeSession(payee.clone(), i),
+            &session.session_id,
+        );
+    }
+
+    fn record_session_locked(env: &Env, session: &Session) {
+        let mut stats = read_stats(env);
+        stats.total_sessions = stats.total_sessions.saturating_add(1);
+        write_stats(env, &stats);
+
+        let key = DataKey::TokenVolume(session.asset.clone());
+        let volume: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&key, &volume.saturating_add(session.amount));
+    }
+
+    /// What was paid out when `session_id` was approved. Fails with
+    /// SessionNotApproved if the escrow has not been released yet.
+    pub fn get_completion_receipt(env: Env, session_id: Bytes) -> Result<CompletionReceipt, Error> {
+        if let Some(receipt) = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CompletionReceipt(env.crypto().sha256(&session_id)))
+        {
+            return Ok(receipt);
+        }
+        if Self::get_session(env, session_id).is_none() {
+            return Err(Error::SessionNotFound);
+        }
+        Err(Error::SessionNotApproved)
+    }
+
+    /// Platform-wide counters of sessions locked, completed and cancelled.
+    pub fn get_stats(env: Env) -> SessionStats {
+        read_stats(&env)
+    }
+
+    /// Total amount ever escrowed in `asset`, excluding platform fees.
+    pub fn get_token_volume(env: Env, asset: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TokenVolume(asset))
+            .unwrap_or(0)
+    }
+
+    /// Share the seller's payout for a session between several payees.
+    /// Only the seller may set it, while the session is still Locked.
+    /// Shares must be positive, name distinct payees and sum to 10000 bps.
+    pub fn set_payout_splits(
+        env: Env,
+        session_id: Bytes,
+        caller: Address,
+        splits: Vec<PayoutSplit>,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env)?;
+        caller.require_auth();
+
+        let mut session =
+            Self::get_session(env.clone(), session_id.clone()).ok_or(Error::SessionNotFound)?;
+
+        if caller != session.payee {
+            return Err(Error::NotAuthorizedParty);
+        }
+        if session.status != SessionStatus::Locked {
+            return Err(Error::InvalidSessionStatus);
+        }
+
+        validate_payout_splits(&splits)?;
+
+        session.payout_splits = splits;
+        session.updated_at = env.ledger().timestamp();
+        save_session(&env, &session);
+        Ok(())
+    }
+
+    /// The payout table for a session; empty when the seller is paid alone.
+    /// Also available as `payout_splits` on the session returned by
+    /// `get_session`.
+    pub fn get_payout_splits(env: Env, session_id: Bytes) -> Vec<PayoutSplit> {
+        Self::get_session(env.clone(), session_id)
+            .map(|session| session.payout_splits)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Escrow a bundle of `n_sessions` sessions up front and return the new
+    /// package id. See the `packages` module.
+    pub fn create_package(
+        env: Env,
+        payer: Address,
+        payee: Address,
+        asset: Address,
+        amount_per_session: i128,
+        n_sessions: u32,
+    ) -> Result<u64, soroban_sdk::Error> {
+        packages::create_package(&env, payer, payee, asset, amount_per_session, n_sessions)
+    }
+
+    /// Buyer confirms the next session of a package, releasing one tranche.
+    pub fn confirm_package_session(
+        env: Env,
+        package_id: u64,
+        caller: Address,
+    ) -> Result<(), soroban_sdk::Error> {
+        packages::confirm_package_session(&env, package_id, caller)
+    }
+
+    /// Cancel an active package, refunding the unconfirmed tranches.
+    pub fn cancel_package(
+        env: Env,
+        package_id: u64,
+        caller: Address,
+    ) -> Result<i128, soroban_sdk::Error> {
+        packages::cancel_package(&env, package_id, caller)
+    }
+
+    pub fn get_package(env: Env, package_id: u64) -> Option<packages::Package> {
+        packages::get_package(&env, package_id)
+    }
+
+    /// Pay `payout` to the session's seller, or across its split table, and
+    /// return the legs paid. Every seller payout goes through here: approval,
+    /// dispute resolution, conditional and milestone release.
+    /// The last leg absorbs rounding so the legs always sum to `payout`.
+    fn pay_payees(
+        env: &Env,
+        token_client: &token::Client,
+        session: &Session,
+        payout: i128,
+    ) -> Result<Vec<PayoutLeg>, Error> {
+        let mut legs = Vec::new(env);
+        if session.payout_splits.is_empty() {
+            legs.push_back(PayoutLeg {
+                payee: session.payee.clone(),
+                amount: payout,
+            });
+        } else {
+            let mut remaining = payout;
+            let last = session.payout_splits.len() - 1;
+            for (i, split) in session.payout_splits.iter().enumerate() {
+                let amount = if i as u32 == last {
+                    remaining
+                } else {
+                    payout
+                        .checked_mul(split.bps as i128)
+                        .ok_or(Error::FeeCalculationOverflow)?
+                        / 10_000
+                };
+                remaining -= amount;
+                legs.push_back(PayoutLeg {
+                    payee: split.payee,
+                    amount,
+                });
+            }
+        }
+
+        let contract_id = env.current_contract_address();
+        for leg in legs.iter() {
+            if leg.amount > 0 {
+                token_client.transfer(&contract_id, &leg.payee, &leg.amount);
+            }
+            env.events().publish(
+                (Symbol::new(env, "PayoutLegPaid"),),
+                PayoutLegPaid {
+                    session_id: session.session_id.clone(),
+                    payee: leg.payee,
+                    amount: leg.amount,
+                },
+            );
+        }
+        Ok(legs)
+    }
+
     // ── Issue #208: Maximum session duration enforcement ─────────────────────
 
     /// Set the maximum session duration in ledgers. Admin only.
@@ -1281,9 +2528,9 @@ impl SkillSyncContract {
 
     /// Cancel a session that has exceeded the maximum session duration.
     /// Anyone can call this after expiry. Refunds buyer fully, no fee.
+    /// Remains available while the contract is paused.
     /// Emits SessionExpiredAndCancelled event. Closes issue #208.
     pub fn cancel_expired_session(env: Env, session_id: Bytes) -> Result<(), Error> {
-        Self::require_not_paused(&env)?;
         acquire_lock(&env)?;
 
         let mut session = Self::get_session(env.clone(), session_id.clone())
@@ -1312,15 +2559,18 @@ impl SkillSyncContract {
             .ok_or(Error::FeeCalculationOverflow)?;
         let total_locked = session.amount.checked_add(fee).ok_or(Error::FeeCalculationOverflow)?;
 
+        session.status = SessionStatus::Cancelled;
+        session.updated_at = env.ledger().timestamp();
+        save_session(&env, &session);
+
+        let mut stats = read_stats(&env);
+        stats.total_cancelled = stats.total_cancelled.saturating_add(1);
+        write_stats(&env, &stats);
+
         let token_client = token::Client::new(&env, &session.asset);
         let contract_id = env.current_contract_address();
         token_client.transfer(&contract_id, &session.payer, &total_locked);
 
-        session.status = SessionStatus::Cancelled;
-        session.updated_at = env.ledger().timestamp();
-        let key = DataKey::Session(session_id.clone());
-        env.storage().persistent().set(&key, &session);
-
         Self::remove_from_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
 
         env.events().publish(
@@ -1362,7 +2612,9 @@ impl SkillSyncContract {
 
         validate_session_id(&session_id)?;
         validate_amount(total_amount)?;
+        validate_amount_limits(&env, total_amount)?;
         validate_different_addresses(&payer, &payee)?;
+        validate_asset(&env, &asset)?;
 
         if milestones.is_empty() {
             release_lock(&env);
@@ -1386,7 +2638,7 @@ impl SkillSyncContract {
         let dispute_window_ledgers = Self::get_dispute_window(env.clone());
         let current_ledger = env.ledger().sequence();
         let dispute_deadline = (current_ledger + dispute_window_ledgers) as u64;
-        let fee_bps = Self::get_platform_fee(env.clone());
+        let fee_bps = Self::get_payee_fee(env.clone(), payee.clone());
         let max_duration = Self::get_max_session_duration(env.clone());
 
         let fee = total_amount
@@ -1414,7 +2666,7 @@ impl SkillSyncContract {
         }
 
         let session = Session {
-            version: VERSION,
+            version: SESSION_VERSION,
             session_id: session_id.clone(),
             payer: payer.clone(),
             payee: payee.clone(),
@@ -1435,17 +2687,20 @@ impl SkillSyncContract {
             resolver: None,
             resolution_note: None,
             pending_extension: None,
+            metadata_hash: None,
+            payout_splits: Vec::new(&env),
         };
 
-        let key = DataKey::Session(session_id.clone());
-        if env.storage().persistent().has(&key) {
+        if session_exists(&env, &session_id) {
             release_lock(&env);
             return Err(Error::DuplicateSessionId);
         }
-        env.storage().persistent().set(&key, &session);
+        save_session(&env, &session);
         env.storage()
             .persistent()
             .set(&DataKey::SessionMilestones(session_id.clone()), &milestone_list);
+        Self::add_to_party_index(&env, &session);
+        Self::record_session_locked(&env, &session);
 
         Self::add_to_expiry_index(env.clone(), session_id.clone(), session.expires_at)?;
 
@@ -1454,7 +2709,16 @@ impl SkillSyncContract {
 
         env.events().publish(
             (Symbol::new(&env, "FundsLockedWithMilestones"),),
-            (session_id, payer, payee, total_amount, fee),
+            FundsLockedWithMilestones {
+                version: EVENT_VERSION,
+                session_id,
+                payer,
+                payee,
+                asset,
+                total_amount,
+                fee,
+                milestone_count: milestone_list.len(),
+            },
         );
 
         release_lock(&env);
@@ -1508,16 +2772,15 @@ impl SkillSyncContract {
             .checked_div(10_000)
             .ok_or(Error::FeeCalculationOverflow)? as i128;
 
-        let token_client = token::Client::new(&env, &session.asset);
-        let contract_id = env.current_contract_address();
-        token_client.transfer(&contract_id, &session.payee, &milestone_amount);
-
         milestone.released = true;
         milestones.set(milestone_index, milestone);
         env.storage()
             .persistent()
             .set(&DataKey::SessionMilestones(session_id.clone()), &milestones);
 
+        let token_client = token::Client::new(&env, &session.asset);
+        Self::pay_payees(&env, &token_client, &session, milestone_amount)?;
+
         env.events().publish(
             (Symbol::new(&env, "MilestoneReleased"),),
             MilestoneReleased {
@@ -1631,6 +2894,155 @@ impl SkillSyncContract {
     }
 }
 
+/// Storage key for a session: the SHA-256 of its id, so key size does not
+/// grow with the id length.
+fn session_key(env: &Env, session_id: &Bytes) -> DataKey {
+    DataKey::SessionByKey(env.crypto().sha256(session_id))
+}
+
+fn session_exists(env: &Env, session_id: &Bytes) -> bool {
+    env.storage().persistent().has(&session_key(env, session_id))
+        || env
+            .storage()
+            .persistent()
+            .has(&DataKey::Session(session_id.clone()))
+}
+
+/// Store a new session, failing if its id is already taken.
+fn insert_session(env: &Env, session: &Session) -> Result<(), Error> {
+    if session_exists(env, &session.session_id) {
+        return Err(Error::DuplicateSessionId);
+    }
+    save_session(env, session);
+    Ok(())
+}
+
+/// Whether the session was written by `put_session` rather than locked by
+/// one of the escrow paths.
+fn is_imported_session(env: &Env, session_id: &Bytes) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::ImportedSession(env.crypto().sha256(session_id)))
+}
+
+fn remove_session(env: &Env, session_id: &Bytes) {
+    env.storage()
+        .persistent()
+        .remove(&session_key(env, session_id));
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Session(session_id.clone()));
+}
+
+/// Write a session under its hashed key. A legacy raw-id entry is left in
+/// place, shadowed by the hashed one, until `migrate_stored_session` drops it.
+fn save_session(env: &Env, session: &Session) {
+    env.storage()
+        .persistent()
+        .set(&session_key(env, &session.session_id), session);
+}
+
+/// Read a stored session from its hashed key, falling back to the legacy
+/// raw-id key, and decode records written before the current layout.
+/// Version 1 records lack `metadata_hash` and version 2 records lack
+/// `payout_splits`; both are upgraded in memory with the new fields
+/// defaulted, and the stored `version` is left unchanged.
+fn load_session(env: &Env, session_id: &Bytes) -> Option<Session> {
+    let raw: Map<Symbol, Val> = env
+        .storage()
+        .persistent()
+        .get(&session_key(env, session_id))
+        .or_else(|| {
+            env.storage()
+                .persistent()
+                .get(&DataKey::Session(session_id.clone()))
+        })?;
+    if raw.contains_key(Symbol::new(env, "payout_splits")) {
+        Session::try_from_val(env, &raw.to_val()).ok()
+    } else if raw.contains_key(Symbol::new(env, "metadata_hash")) {
+        SessionV2::try_from_val(env, &raw.to_val())
+            .ok()
+            .map(Session::from)
+    } else {
+        SessionV1::try_from_val(env, &raw.to_val())
+            .ok()
+            .map(Session::from)
+    }
+}
+
+/// Decode a session with `load_session` and, if it predates SESSION_VERSION
+/// or still sits under the legacy raw-id key, write it back in the current
+/// layout under its hashed key, drop the legacy entry and emit
+/// SessionMigratedEvent.
+fn migrate_stored_session(env: &Env, session_id: Bytes) -> Result<bool, Error> {
+    let mut session = load_session(env, &session_id).ok_or(Error::SessionNotFound)?;
+    let legacy_key = env
+        .storage()
+        .persistent()
+        .has(&DataKey::Session(session_id.clone()));
+
+    if session.version >= SESSION_VERSION && !legacy_key {
+        return Ok(false);
+    }
+
+    let from_version = session.version;
+    session.version = SESSION_VERSION;
+    save_session(env, &session);
+    if legacy_key {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Session(session_id.clone()));
+    }
+
+    env.events().publish(
+        (Symbol::new(env, "SessionMigrated"),),
+        SessionMigratedEvent {
+            session_id,
+            from_version,
+            to_version: SESSION_VERSION,
+        },
+    );
+
+    Ok(true)
+}
+
+fn record_completion_receipt(
+    env: &Env,
+    session: &Session,
+    paid_amount: i128,
+    fee: i128,
+    treasury: &Address,
+    legs: Vec<PayoutLeg>,
+) {
+    let receipt = CompletionReceipt {
+        session_id: session.session_id.clone(),
+        payee: session.payee.clone(),
+        asset: session.asset.clone(),
+        paid_amount,
+        fee,
+        treasury: treasury.clone(),
+        completed_at: session.approved_at,
+        legs,
+    };
+    env.storage()
+        .persistent()
+        .set(
+            &DataKey::CompletionReceipt(env.crypto().sha256(&session.session_id)),
+            &receipt,
+        );
+}
+
+fn read_stats(env: &Env) -> SessionStats {
+    env.storage()
+        .instance()
+        .get(&DataKey::Stats)
+        .unwrap_or_default()
+}
+
+fn write_stats(env: &Env, stats: &SessionStats) {
+    env.storage().instance().set(&DataKey::Stats, stats);
+}
+
 fn read_admin(env: &Env) -> Result<Address, Error> {
     env.storage()
         .instance()
@@ -1638,6 +3050,16 @@ fn read_admin(env: &Env) -> Result<Address, Error> {
         .ok_or(Error::NotInitialized)
 }
 
+/// Require `caller`'s auth and that it is the admin or holds `role`.
+fn require_role(env: &Env, role: Bytes, caller: &Address) -> Result<(), Error> {
+    caller.require_auth();
+    if *caller == read_admin(env)? || rbac::has_role(env, role, caller.clone()) {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
 fn acquire_lock(env: &Env) -> Result<(), Error> {
     if env
         .storage()
@@ -1659,6 +3081,67 @@ fn release_lock(env: &Env) {
         .set(&DataKey::ReentrancyLock, &false);
 }
 
+/// Number of entries in a per-entry keyed index.
+fn read_index_count(env: &Env, count_key: &DataKey) -> u32 {
+    env.storage().persistent().get(count_key).unwrap_or(0)
+}
+
+/// Store `id` as the next entry of a per-entry keyed index. Each id gets its
+/// own storage entry, so the cost of an append does not grow with the index.
+fn append_to_index(env: &Env, count_key: &DataKey, entry_key: impl Fn(u32) -> DataKey, id: &Bytes) {
+    let count = read_index_count(env, count_key);
+    env.storage().persistent().set(&entry_key(count), id);
+    env.storage().persistent().set(count_key, &(count + 1));
+}
+
+/// Returns the `page`-th slice of a per-entry keyed index holding `count`
+/// ids, at most MAX_PAGE_LIMIT long.
+fn paginate_index(
+    env: &Env,
+    count: u32,
+    page: u32,
+    limit: u32,
+    entry_key: impl Fn(u32) -> DataKey,
+) -> Vec<Bytes> {
+    let mut ids = Vec::new(env);
+    let limit = limit.min(MAX_PAGE_LIMIT);
+    let start = page.saturating_mul(limit);
+    if limit == 0 || start >= count {
+        return ids;
+    }
+    let end = start.saturating_add(limit).min(count);
+    for i in start..end {
+        if let Some(id) = env.storage().persistent().get(&entry_key(i)) {
+            ids.push_back(id);
+        }
+    }
+    ids
+}
+
+/// Sum of milestone payments already released to the seller for a session.
+fn released_milestone_total(env: &Env, session_id: &Bytes, amount: i128) -> Result<i128, Error> {
+    let milestones: Vec<Milestone> = match env
+        .storage()
+        .persistent()
+        .get(&DataKey::SessionMilestones(session_id.clone()))
+    {
+        Some(milestones) => milestones,
+        None => return Ok(0),
+    };
+
+    let mut total: i128 = 0;
+    for milestone in milestones.iter() {
+        if milestone.released {
+            let paid = amount
+                .checked_mul(milestone.percentage_bps as i128)
+                .ok_or(Error::FeeCalculationOverflow)?
+                / 10_000;
+            total = total.checked_add(paid).ok_or(Error::FeeCalculationOverflow)?;
+        }
+    }
+    Ok(total)
+}
+
 fn use_nonce(env: &Env, addr: &Address, nonce: u64) -> Result<(), Error> {
     let key = DataKey::Nonce(addr.clone());
     let current = env.storage().persistent().get(&key).unwrap_or(0);
@@ -1704,6 +3187,54 @@ fn validate_amount(amount: i128) -> Result<(), Error> {
     Ok(())
 }
 
+fn validate_amount_limits(env: &Env, amount: i128) -> Result<(), Error> {
+    let (min_amount, max_amount) = SkillSyncContract::get_amount_limits(env.clone());
+    if amount < min_amount {
+        return Err(Error::AmountBelowMinimum);
+    }
+    if amount > max_amount {
+        return Err(Error::AmountAboveMaximum);
+    }
+    Ok(())
+}
+
+/// The native asset's contract address, derived from the XDR encoding of
+/// `Asset::Native` (a zero discriminant).
+fn native_asset_address(env: &Env) -> Address {
+    env.deployer()
+        .with_stellar_asset(Bytes::from_array(env, &[0u8; 4]))
+        .deployed_address()
+}
+
+fn validate_asset(env: &Env, asset: &Address) -> Result<(), Error> {
+    if !SkillSyncContract::is_token_allowed(env.clone(), asset.clone()) {
+        return Err(Error::AssetNotAllowed);
+    }
+    Ok(())
+}
+
+fn validate_payout_splits(splits: &Vec<PayoutSplit>) -> Result<(), Error> {
+    if splits.is_empty() || splits.len() > MAX_PAYOUT_SPLITS {
+        return Err(Error::InvalidAmount);
+    }
+    let mut total: u32 = 0;
+    for (i, split) in splits.iter().enumerate() {
+        if split.bps == 0 {
+            return Err(Error::InvalidAmount);
+        }
+        total = total.checked_add(split.bps).ok_or(Error::InvalidAmount)?;
+        for other in splits.iter().skip(i + 1) {
+            if other.payee == split.payee {
+                return Err(Error::InvalidAddress);
+            }
+        }
+    }
+    if total != 10_000 {
+        return Err(Error::InvalidAmount);
+    }
+    Ok(())
+}
+
 fn validate_different_addresses(addr1: &Address, addr2: &Address) -> Result<(), Error> {
     if addr1 == addr2 {
         return Err(Error::InvalidAddress);
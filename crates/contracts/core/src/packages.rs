@@ -0,0 +1,305 @@
+/// Session packages module — recurring mentorship bundles
+///
+/// A package escrows `n_sessions` tranches of `amount_per_session`, plus the
+/// payee's platform fee on each, in a single transfer. The buyer confirms
+/// each delivered session with `confirm_package_session`, which pays one
+/// tranche to the seller and its fee to the treasury. `cancel_package`
+/// returns every unconfirmed tranche to the buyer.
+///
+/// The entrypoints are declared in lib.rs and delegate to the functions here.
+use soroban_sdk::{contracttype, symbol_short, token, Address, Env};
+
+use crate::{Error, PackageError, SkillSyncContract, EVENT_VERSION};
+
+pub const MAX_PACKAGE_SESSIONS: u32 = 52; // One year of weekly sessions
+
+// ── Storage keys ──────────────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum PackageKey {
+    Package(u64),
+    NextId,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PackageStatus {
+    Active,
+    Completed,
+    Cancelled,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Package {
+    pub package_id: u64,
+    pub payer: Address,
+    pub payee: Address,
+    pub asset: Address,
+    /// Paid to the payee for each confirmed session, excluding the fee.
+    pub amount_per_session: i128,
+    pub fee_bps: u32,
+    pub total_sessions: u32,
+    pub released_sessions: u32,
+    pub status: PackageStatus,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+// ── Events ────────────────────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PackageCreatedEvent {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    pub package_id: u64,
+    pub payer: Address,
+    pub payee: Address,
+    pub amount_per_session: i128,
+    pub total_sessions: u32,
+    pub total_locked: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PackageTrancheReleasedEvent {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    pub package_id: u64,
+    pub session_number: u32,
+    pub payee: Address,
+    pub amount: i128,
+    pub fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PackageCancelledEvent {
+    /// Event layout version, see `EVENT_VERSION`.
+    pub version: u32,
+    pub package_id: u64,
+    pub cancelled_by: Address,
+    pub refunded_sessions: u32,
+    pub refund: i128,
+}
+
+// ── Implementation ────────────────────────────────────────────────────────────
+
+/// Escrow a bundle of `n_sessions` sessions up front and return the new
+/// package id.
+pub(crate) fn create_package(
+    env: &Env,
+    payer: Address,
+    payee: Address,
+    asset: Address,
+    amount_per_session: i128,
+    n_sessions: u32,
+) -> Result<u64, soroban_sdk::Error> {
+    SkillSyncContract::require_not_paused(env)?;
+    payer.require_auth();
+
+    crate::validate_amount(amount_per_session)?;
+    crate::validate_amount_limits(env, amount_per_session)?;
+    crate::validate_different_addresses(&payer, &payee)?;
+    crate::validate_asset(env, &asset)?;
+    if n_sessions == 0 || n_sessions > MAX_PACKAGE_SESSIONS {
+        return Err(PackageError::InvalidPackageSize.into());
+    }
+
+    crate::acquire_lock(env)?;
+
+    let fee_bps = SkillSyncContract::get_payee_fee(env.clone(), payee.clone());
+    let total_locked = tranche_total(amount_per_session, fee_bps)?
+        .checked_mul(n_sessions as i128)
+        .ok_or(Error::FeeCalculationOverflow)?;
+
+    let token_client = token::Client::new(env, &asset);
+    if token_client.balance(&payer) < total_locked {
+        crate::release_lock(env);
+        return Err(Error::InsufficientBalance.into());
+    }
+
+    let package_id: u64 = env
+        .storage()
+        .instance()
+        .get(&PackageKey::NextId)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&PackageKey::NextId, &(package_id + 1));
+
+    let now = env.ledger().timestamp();
+    let package = Package {
+        package_id,
+        payer: payer.clone(),
+        payee: payee.clone(),
+        asset,
+        amount_per_session,
+        fee_bps,
+        total_sessions: n_sessions,
+        released_sessions: 0,
+        status: PackageStatus::Active,
+        created_at: now,
+        updated_at: now,
+    };
+    env.storage()
+        .persistent()
+        .set(&PackageKey::Package(package_id), &package);
+
+    let contract_id = env.current_contract_address();
+    token_client.transfer(&payer, &contract_id, &total_locked);
+
+    env.events().publish(
+        (symbol_short!("pkg_new"),),
+        PackageCreatedEvent {
+            version: EVENT_VERSION,
+            package_id,
+            payer,
+            payee,
+            amount_per_session,
+            total_sessions: n_sessions,
+            total_locked,
+        },
+    );
+
+    crate::release_lock(env);
+    Ok(package_id)
+}
+
+/// Buyer confirms that the next session in the package took place,
+/// releasing one tranche. The package completes with the last tranche.
+pub(crate) fn confirm_package_session(
+    env: &Env,
+    package_id: u64,
+    caller: Address,
+) -> Result<(), soroban_sdk::Error> {
+    SkillSyncContract::require_not_paused(env)?;
+    caller.require_auth();
+
+    let key = PackageKey::Package(package_id);
+    let mut package: Package = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(PackageError::PackageNotFound)?;
+
+    if caller != package.payer {
+        return Err(Error::NotAuthorizedParty.into());
+    }
+    if package.status != PackageStatus::Active {
+        return Err(PackageError::PackageNotActive.into());
+    }
+
+    crate::acquire_lock(env)?;
+
+    let fee =
+        tranche_total(package.amount_per_session, package.fee_bps)? - package.amount_per_session;
+
+    package.released_sessions += 1;
+    if package.released_sessions == package.total_sessions {
+        package.status = PackageStatus::Completed;
+    }
+    package.updated_at = env.ledger().timestamp();
+    env.storage().persistent().set(&key, &package);
+
+    let token_client = token::Client::new(env, &package.asset);
+    let contract_id = env.current_contract_address();
+    token_client.transfer(&contract_id, &package.payee, &package.amount_per_session);
+    if fee > 0 {
+        let treasury = SkillSyncContract::get_treasury(env.clone());
+        token_client.transfer(&contract_id, &treasury, &fee);
+    }
+
+    env.events().publish(
+        (symbol_short!("pkg_paid"),),
+        PackageTrancheReleasedEvent {
+            version: EVENT_VERSION,
+            package_id,
+            session_number: package.released_sessions,
+            payee: package.payee,
+            amount: package.amount_per_session,
+            fee,
+        },
+    );
+
+    crate::release_lock(env);
+    Ok(())
+}
+
+/// Cancel an active package and refund the unconfirmed tranches, fees
+/// included, to the buyer. Callable by the buyer or the admin; stays
+/// available while the contract is paused.
+pub(crate) fn cancel_package(
+    env: &Env,
+    package_id: u64,
+    caller: Address,
+) -> Result<i128, soroban_sdk::Error> {
+    caller.require_auth();
+
+    let key = PackageKey::Package(package_id);
+    let mut package: Package = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(PackageError::PackageNotFound)?;
+
+    if caller != package.payer && caller != crate::read_admin(env)? {
+        return Err(Error::NotAuthorizedParty.into());
+    }
+    if package.status != PackageStatus::Active {
+        return Err(PackageError::PackageNotActive.into());
+    }
+
+    crate::acquire_lock(env)?;
+
+    let remaining = package.total_sessions - package.released_sessions;
+    let refund = tranche_total(package.amount_per_session, package.fee_bps)?
+        .checked_mul(remaining as i128)
+        .ok_or(Error::FeeCalculationOverflow)?;
+
+    package.status = PackageStatus::Cancelled;
+    package.updated_at = env.ledger().timestamp();
+    env.storage().persistent().set(&key, &package);
+
+    if refund > 0 {
+        let token_client = token::Client::new(env, &package.asset);
+        let contract_id = env.current_contract_address();
+        token_client.transfer(&contract_id, &package.payer, &refund);
+    }
+
+    env.events().publish(
+        (symbol_short!("pkg_end"),),
+        PackageCancelledEvent {
+            version: EVENT_VERSION,
+            package_id,
+            cancelled_by: caller,
+            refunded_sessions: remaining,
+            refund,
+        },
+    );
+
+    crate::release_lock(env);
+    Ok(refund)
+}
+
+pub(crate) fn get_package(env: &Env, package_id: u64) -> Option<Package> {
+    env.storage()
+        .persistent()
+        .get(&PackageKey::Package(package_id))
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// One session's amount plus its platform fee.
+fn tranche_total(amount_per_session: i128, fee_bps: u32) -> Result<i128, Error> {
+    let fee = amount_per_session
+        .checked_mul(fee_bps as i128)
+        .ok_or(Error::FeeCalculationOverflow)?
+        .checked_div(10_000)
+        .ok_or(Error::FeeCalculationOverflow)?;
+    amount_per_session
+        .checked_add(fee)
+        .ok_or(Error::FeeCalculationOverflow)
+}
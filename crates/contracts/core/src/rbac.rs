@@ -16,6 +16,12 @@ pub mod roles {
     pub fn upgrader(env: &Env) -> Bytes {
         Bytes::from_slice(env, b"UPGRADER_ROLE")
     }
+    pub fn pauser(env: &Env) -> Bytes {
+        Bytes::from_slice(env, b"PAUSER_ROLE")
+    }
+    pub fn treasurer(env: &Env) -> Bytes {
+        Bytes::from_slice(env, b"TREASURER_ROLE")
+    }
 }
 
 #[contracttype]
@@ -125,9 +125,7 @@ impl SkillSyncContract {
             .set(&ArchiveKey::Archived(session_id.clone()), &archive);
 
         // Remove the full session record.
-        env.storage()
-            .persistent()
-            .remove(&DataKey::Session(session_id.clone()));
+        crate::remove_session(&env, &session_id);
 
         env.events().publish(
             (symbol_short!("archived"),),
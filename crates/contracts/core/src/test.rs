@@ -1,45 +1,18 @@
 #![cfg(test)]
 
-use super::*;
-use crate::{SkillSyncContract, SkillSyncContractClient, DEFAULT_DISPUTE_WINDOW_SECONDS, DEFAULT_DISPUTE_WINDOW_LEDGERS, DISPUTE_WINDOW_MIN_LEDGERS, DISPUTE_WINDOW_MAX_LEDGERS};
-use soroban_sdk::{symbol_short, testutils::Address as _, vec, Address, BytesN, Env};
 extern crate std;
 
-use crate::{AutoRefundExecutedEvent, CoreContract, CoreContractClient, SessionStatus};
+use super::*;
 use soroban_sdk::{
-    bytesn,
-    testutils::{Address as _, Events as _},
     testutils::{Address as _, Events as _, Ledger as _},
     token::{Client as TokenClient, StellarAssetClient},
-    Address, BytesN, Env,
+    symbol_short, vec, Address, Bytes, Env,
 };
 
-fn lock_session_id(env: &Env) -> BytesN<32> {
-    bytesn!(
-        env,
-        0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20
-    )
-}
-
-fn setup() -> (
-    Env,
-    CoreContractClient<'static>,
-    TokenClient<'static>,
-    StellarAssetClient<'static>,
-    Address,
-    Address,
-    Address,
-    Address,
-) {
-    setup_with_fee(500) // Default 5% fee
-}
-
-fn setup_with_admin() -> (
+fn setup_escrow() -> (
     Env,
-    CoreContractClient<'static>,
+    SkillSyncContractClient<'static>,
     TokenClient<'static>,
-    StellarAssetClient<'static>,
-    Address,
     Address,
     Address,
     Address,
@@ -48,1522 +21,2187 @@ fn setup_with_admin() -> (
     let env = Env::default();
     env.mock_all_auths();
 
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
     let buyer = Address::generate(&env);
     let seller = Address::generate(&env);
-    let treasury = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let token_admin = Address::generate(&env);
 
-    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_address = env.register_stellar_asset_contract(Address::generate(&env));
     let token_client = TokenClient::new(&env, &token_address);
-    let asset_client = StellarAssetClient::new(&env, &token_address);
+    StellarAssetClient::new(&env, &token_address).mint(&buyer, &10_000);
 
     let contract_id = env.register_contract(None, SkillSyncContract);
     let contract = SkillSyncContractClient::new(&env, &contract_id);
     contract.init(&admin, &500, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS);
+    contract.add_allowed_token(&token_address);
 
-    (
-        env,
-        contract,
-        token_client,
-        asset_client,
-        buyer,
-        seller,
-        treasury,
-        contract_id,
-        admin,
-    )
-}
-
-fn setup_with_fee(
-    fee_bps: u32,
-) -> (
-    Env,
-    CoreContractClient<'static>,
-    TokenClient<'static>,
-    StellarAssetClient<'static>,
-    Address,
-    Address,
-    Address,
-    Address,
-) {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let buyer = Address::generate(&env);
-    let seller = Address::generate(&env);
-    let treasury = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-
-    let token_address = env.register_stellar_asset_contract(token_admin.clone());
-    let token_client = TokenClient::new(&env, &token_address);
-    let asset_client = StellarAssetClient::new(&env, &token_address);
-
-    let contract_id = env.register_contract(None, SkillSyncContract);
-    let contract = SkillSyncContractClient::new(&env, &contract_id);
-    contract.init(&admin, &fee_bps, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS);
+    (env, contract, token_client, admin, treasury, buyer, seller)
+}
 
-    (
-        env,
-        contract,
-        token_client,
-        asset_client,
-        buyer,
-        seller,
-        treasury,
-        contract_id,
-    )
+fn advance_ledgers(env: &Env, ledgers: u32) {
+    env.ledger().with_mut(|l| {
+        l.sequence_number += ledgers;
+        l.timestamp += ledgers as u64 * 5;
+    });
 }
 
-fn mint_and_approve(asset_client: &StellarAssetClient<'static>, buyer: &Address, amount: i128) {
-    asset_client.mint(buyer, &amount);
+/// Data of the first event published under `name` by the last invocation.
+fn find_event(env: &Env, name: &str) -> Option<Val> {
+    let name = Symbol::new(env, name);
+    env.events().all().iter().find_map(|(_, topics, data)| {
+        let topic = Symbol::try_from_val(env, &topics.get(0)?).ok()?;
+        (topic == name).then_some(data)
+    })
 }
 
 // ============================================================================
-// Test 5a: Happy Path - Create → Complete → Approve
+// Test: Initialization
 // ============================================================================
 
 #[test]
-fn test_happy_path_create_complete_approve() {
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) = setup();
-
-    // Fund buyer
-    mint_and_approve(&asset_client, &buyer, 1_000);
-
-    // Create session
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    assert_eq!(token_client.balance(&buyer), 0);
-    assert_eq!(token_client.balance(&contract_id), 1_000);
-
-    // Complete session
-    contract.complete_session(&session_id, &seller, &0);
+fn init_stores_configuration_and_emits_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
 
-    // Approve session
-    contract.approve_session(&session_id, &buyer, &1);
+    let contract_id = env.register_contract(None, SkillSyncContract);
+    let contract = SkillSyncContractClient::new(&env, &contract_id);
+    contract.init(&admin, &500, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS);
 
-    // Verify balances
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Approved));
-    assert_eq!(token_client.balance(&buyer), 0);
-    assert_eq!(token_client.balance(&seller), 950); // 1000 - 5% fee
-    assert_eq!(token_client.balance(&treasury), 50); // 5% fee
-    assert_eq!(token_client.balance(&contract_id), 0);
+    let event = Initialized::try_from_val(&env, &find_event(&env, "Initialized").unwrap()).unwrap();
+    assert_eq!(event.admin, admin);
+    assert_eq!(event.treasury, treasury);
 
-    // Verify event
-    let events = env.events().all();
-    let last_event = events.last().unwrap();
-    assert_eq!(last_event.0, contract_id);
-    assert!(std::format!("{:?}", last_event.1).contains("approved"));
+    assert_eq!(contract.get_platform_fee(), 500);
+    assert_eq!(contract.get_treasury(), treasury);
+    assert_eq!(contract.get_dispute_window(), DEFAULT_DISPUTE_WINDOW_LEDGERS);
+    assert!(!contract.is_paused());
+    assert_eq!(
+        contract.try_init(&admin, &500, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS),
+        Err(Ok(Error::AlreadyInitialized))
+    );
 }
 
 #[test]
-fn test_pause_blocks_state_changes_but_allows_view() {
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id, _admin) =
-        setup_with_admin();
+fn init_validates_fee_and_dispute_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
 
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    contract.pause().unwrap();
-    assert!(contract.is_paused());
-    assert_eq!(contract.get_platform_fee(), 500);
+    let contract_id = env.register_contract(None, SkillSyncContract);
+    let contract = SkillSyncContractClient::new(&env, &contract_id);
 
-    let result = env.try_invoke_contract(
-        &contract_id,
-        &"create_session",
-        buyer.clone().into_val(&env),
-        seller.clone().into_val(&env),
-        token_client.address.into_val(&env),
-        (1_000_i128).into_val(&env),
+    assert_eq!(
+        contract.try_init(&admin, &(PLATFORM_FEE_MAX_BPS + 1), &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS),
+        Err(Ok(Error::InvalidFeeBps))
+    );
+    assert_eq!(
+        contract.try_init(&admin, &500, &treasury, &(DISPUTE_WINDOW_MIN_LEDGERS - 1)),
+        Err(Ok(Error::InvalidDisputeWindow))
     );
-    assert!(result.is_err());
-
-    contract.unpause().unwrap();
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    assert_eq!(token_client.balance(&contract_id), 1_000);
-    assert!(!contract.is_paused());
 }
 
-// ============================================================================
-// Test 5b: Refund Path - Create → Refund Initiate → Refund Approve
-// ============================================================================
-
 #[test]
-fn test_refund_path_initiate_approve() {
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) = setup();
-
-    // Fund buyer
-    mint_and_approve(&asset_client, &buyer, 1_000);
-
-    // Create session
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    assert_eq!(token_client.balance(&contract_id), 1_000);
-
-    // Buyer initiates refund
-    contract.refund_initiate(&session_id);
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::RefundRequested));
+fn uninitialized_contract_rejects_admin_calls() {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    // Seller approves refund
-    contract.refund_approve(&session_id);
+    let contract_id = env.register_contract(None, SkillSyncContract);
+    let contract = SkillSyncContractClient::new(&env, &contract_id);
 
-    // Verify balances - full refund, no fees
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Refunded));
-    assert_eq!(token_client.balance(&buyer), 1_000);
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 0);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(
+        contract.try_set_platform_fee(&100),
+        Err(Ok(Error::NotInitialized))
+    );
+    assert!(contract.get_session(&Bytes::from_slice(&env, b"missing")).is_none());
 }
 
 // ============================================================================
-// Test 5c: Refund After Completion
+// Test: Escrow lifecycle
 // ============================================================================
 
 #[test]
-fn test_refund_after_completion() {
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) = setup();
-
-    // Fund buyer
-    mint_and_approve(&asset_client, &buyer, 1_000);
-
-    // Create and complete session
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+fn lock_complete_approve_pays_seller_and_treasury() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"happy_path");
 
-    // Buyer initiates refund after completion
-    contract.refund_initiate(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(token_client.balance(&buyer), 8_950);
+    assert_eq!(token_client.balance(&contract.address), 1_050);
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Locked
+    );
 
-    // Seller approves refund
-    contract.refund_approve(&session_id);
+    contract.complete_session(&session_id, &seller, &1);
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Completed
+    );
 
-    // Verify full refund
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Refunded));
-    assert_eq!(token_client.balance(&buyer), 1_000);
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 0);
+    contract.approve_session(&session_id, &buyer, &1);
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.status, SessionStatus::Approved);
+    assert_eq!(session.approved_at, env.ledger().timestamp());
+    assert_eq!(token_client.balance(&seller), 1_000);
+    assert_eq!(token_client.balance(&treasury), 50);
+    assert_eq!(token_client.balance(&contract.address), 0);
 }
 
-// ============================================================================
-// Test 5d: Dispute Path - Create → Complete → Dispute → Resolve
-// ============================================================================
-
 #[test]
-fn test_dispute_initiate_and_resolve() {
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) = setup();
-
-    // Fund buyer
-    mint_and_approve(&asset_client, &buyer, 1_000);
+fn concurrent_sessions_do_not_interfere() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let seller2 = Address::generate(&env);
+    let first = Bytes::from_slice(&env, b"concurrent_1");
+    let second = Bytes::from_slice(&env, b"concurrent_2");
 
-    // Create and complete session
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+    contract.lock_funds(&first, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.lock_funds(&second, &buyer, &seller2, &token_client.address, &2_000, &None);
 
-    // Buyer initiates dispute
-    contract.dispute_initiate(&session_id);
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Disputed));
+    // Complete and approve in opposite orders.
+    contract.complete_session(&second, &seller2, &1);
+    contract.complete_session(&first, &seller, &1);
+    contract.approve_session(&first, &buyer, &1);
 
-    // Treasury resolves: 600 to buyer, 400 to seller
-    contract.dispute_resolve(&session_id, &600);
+    assert_eq!(
+        contract.get_session(&first).unwrap().status,
+        SessionStatus::Approved
+    );
+    assert_eq!(
+        contract.get_session(&second).unwrap().status,
+        SessionStatus::Completed
+    );
+    assert_eq!(token_client.balance(&seller), 1_000);
+    assert_eq!(token_client.balance(&seller2), 0);
 
-    // Verify distribution
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Approved));
-    assert_eq!(token_client.balance(&buyer), 600);
-    assert_eq!(token_client.balance(&seller), 400);
-    assert_eq!(token_client.balance(&treasury), 0); // No fee on disputed amount
-    assert_eq!(token_client.balance(&contract_id), 0);
+    contract.approve_session(&second, &buyer, &2);
+    assert_eq!(token_client.balance(&seller2), 2_000);
+    assert_eq!(token_client.balance(&treasury), 150);
 }
 
-// ============================================================================
-// Test 5e: Multiple Concurrent Sessions - No Interference
-// ============================================================================
-
 #[test]
-fn test_multiple_concurrent_sessions_no_interference() {
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) = setup();
+fn zero_fee_session_pays_seller_in_full() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"zero_fee");
+    contract.set_platform_fee(&0);
 
-    // Create 3 different buyers and sellers
-    let buyer2 = Address::generate(&env);
-    let buyer3 = Address::generate(&env);
-    let seller2 = Address::generate(&env);
-    let seller3 = Address::generate(&env);
-
-    // Fund all buyers
-    mint_and_approve(&asset_client, &buyer, 500);
-    mint_and_approve(&asset_client, &buyer2, 1_000);
-    mint_and_approve(&asset_client, &buyer3, 1_500);
-
-    // Create 3 sessions with different amounts
-    let session1 = contract.create_session(&buyer, &seller, &token_client.address, &500);
-    let session2 = contract.create_session(&buyer2, &seller2, &token_client.address, &1_000);
-    let session3 = contract.create_session(&buyer3, &seller3, &token_client.address, &1_500);
-
-    // Complete in random order: 2, 1, 3
-    contract.complete_session(&session2);
-    contract.complete_session(&session1);
-    contract.complete_session(&session3);
-
-    // Approve in different order: 3, 1, 2
-    contract.approve_session(&session3);
-    contract.approve_session(&session1);
-    contract.approve_session(&session2);
-
-    // Verify each session independently
-    let s1 = contract.get_session(&session1);
-    let s2 = contract.get_session(&session2);
-    let s3 = contract.get_session(&session3);
-
-    assert!(matches!(s1.status, SessionStatus::Approved));
-    assert!(matches!(s2.status, SessionStatus::Approved));
-    assert!(matches!(s3.status, SessionStatus::Approved));
-
-    // Verify balances
-    assert_eq!(token_client.balance(&seller), 475); // 500 - 5%
-    assert_eq!(token_client.balance(&seller2), 950); // 1000 - 5%
-    assert_eq!(token_client.balance(&seller3), 1425); // 1500 - 5%
-
-    // Total treasury: 25 + 50 + 75 = 150
-    assert_eq!(token_client.balance(&treasury), 150);
-    assert_eq!(token_client.balance(&contract_id), 0);
-}
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
 
-// ============================================================================
-// Test 5f: Fee Accumulation in Treasury
-// ============================================================================
+    assert_eq!(token_client.balance(&buyer), 9_000);
+    assert_eq!(token_client.balance(&seller), 1_000);
+    assert_eq!(token_client.balance(&treasury), 0);
+    assert_eq!(token_client.balance(&contract.address), 0);
+}
 
 #[test]
-fn test_fee_accumulation_in_treasury() {
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) =
-        setup_with_fee(300);
+fn fee_accumulation_in_treasury() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    contract.set_platform_fee(&300);
 
-    // Create 5 sessions with varying amounts
-    let amounts = vec![200, 400, 600, 800, 1000];
     let mut expected_treasury: i128 = 0;
+    for (nonce, amount) in [200_i128, 400, 600, 800, 1_000].iter().enumerate() {
+        let nonce = nonce as u64 + 1;
+        let session_id = Bytes::from_array(&env, &[b'f', nonce as u8]);
+        contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, amount, &None);
+        contract.complete_session(&session_id, &seller, &nonce);
+        contract.approve_session(&session_id, &buyer, &nonce);
 
-    for amount in &amounts {
-        mint_and_approve(&asset_client, &buyer, *amount);
-        let session_id = contract.create_session(&buyer, &seller, &token_client.address, &amount);
-        contract.complete_session(&session_id);
-        contract.approve_session(&session_id);
-
-        // Fee = amount * 300 / 10000
         expected_treasury += amount * 300 / 10_000;
     }
 
-    // Verify treasury accumulation
     assert_eq!(token_client.balance(&treasury), expected_treasury);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(token_client.balance(&contract.address), 0);
 }
 
 #[test]
-fn test_fee_edge_cases() {
-    // Test with 0% fee
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) =
-        setup_with_fee(0);
-
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
-    contract.approve_session(&session_id);
-
-    assert_eq!(token_client.balance(&seller), 1_000);
-    assert_eq!(token_client.balance(&treasury), 0);
-
-    // Test with 100% fee (10000 bps)
-    let (env, contract, token_client, asset_client, buyer, seller, treasury, contract_id) =
-        setup_with_fee(10_000);
+fn fee_rounds_down() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"fee_rounding");
+    contract.set_platform_fee(&3);
 
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
-    contract.approve_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &5_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
 
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 1_000);
+    assert_eq!(token_client.balance(&seller), 5_000);
+    assert_eq!(token_client.balance(&treasury), 1);
 }
 
-// ============================================================================
-// Test 5g: Invalid State Transitions
-// ============================================================================
-
 #[test]
-#[should_panic(expected = "session must be pending")]
-fn test_cannot_complete_already_completed() {
-    let (_, contract, token_client, asset_client, buyer, seller, _, _) = setup();
+fn approve_session_records_buyer_authorization() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"approve_auth");
 
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
 
-    // Try to complete again - should panic
-    contract.complete_session(&session_id);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, buyer);
 }
 
 #[test]
-#[should_panic(expected = "session must be completed")]
-fn test_cannot_approve_pending_session() {
-    let (_, contract, token_client, asset_client, buyer, seller, _, _) = setup();
+fn lock_funds_rejects_invalid_input() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"invalid_input");
 
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
+    assert_eq!(
+        contract.try_lock_funds(&session_id, &buyer, &seller, &token_client.address, &0, &None),
+        Err(Ok(Error::InvalidAmount))
+    );
+    assert_eq!(
+        contract.try_lock_funds(&session_id, &buyer, &buyer, &token_client.address, &1_000, &None),
+        Err(Ok(Error::InvalidAddress))
+    );
+    assert_eq!(
+        contract.try_lock_funds(&session_id, &buyer, &seller, &token_client.address, &10_000, &None),
+        Err(Ok(Error::InsufficientBalance))
+    );
 
-    // Try to approve without completing - should panic
-    contract.approve_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(
+        contract.try_lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None),
+        Err(Ok(Error::DuplicateSessionId))
+    );
 }
 
 #[test]
-#[should_panic(expected = "session must be pending or completed")]
-fn test_cannot_refund_approved_session() {
-    let (_, contract, token_client, asset_client, buyer, seller, _, _) = setup();
-
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
-    contract.approve_session(&session_id);
-
-    // Try to refund after approval - should panic
-    contract.refund_initiate(&session_id);
-}
+fn invalid_state_transitions_are_rejected() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"transitions");
+    let missing = Bytes::from_slice(&env, b"missing");
 
-#[test]
-#[should_panic(expected = "session must be pending or completed")]
-fn test_cannot_dispute_refunded_session() {
-    let (_, contract, token_client, asset_client, buyer, seller, _, _) = setup();
+    assert_eq!(
+        contract.try_complete_session(&missing, &seller, &1),
+        Err(Ok(Error::SessionNotFound.into()))
+    );
 
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.refund_initiate(&session_id);
-    contract.refund_approve(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(
+        contract.try_approve_session(&session_id, &buyer, &1),
+        Err(Ok(Error::InvalidSessionStatus))
+    );
 
-    // Try to dispute after refund - should panic
-    contract.dispute_initiate(&session_id);
+    contract.complete_session(&session_id, &seller, &2);
+    contract.approve_session(&session_id, &buyer, &2);
+    assert_eq!(
+        contract.try_open_dispute(&session_id, &buyer, &Bytes::new(&env)),
+        Err(Ok(Error::InvalidSessionStatus.into()))
+    );
+    assert_eq!(
+        contract.try_auto_refund(&session_id),
+        Err(Ok(Error::InvalidSessionStatus))
+    );
 }
 
 #[test]
-#[should_panic(expected = "buyer and seller must differ")]
-fn test_cannot_create_session_with_same_buyer_seller() {
-    let (_, contract, token_client, asset_client, buyer, _, _, _) = setup();
+fn completion_and_approval_nonces_cannot_be_replayed() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let first = Bytes::from_slice(&env, b"nonce_1");
+    let second = Bytes::from_slice(&env, b"nonce_2");
 
-    mint_and_approve(&asset_client, &buyer, 1_000);
+    contract.lock_funds(&first, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.lock_funds(&second, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&first, &seller, &5);
 
-    // Try to create session where buyer == seller - should panic
-    contract.create_session(&buyer, &buyer, &token_client.address, &1_000);
+    assert_eq!(
+        contract.try_complete_session(&second, &seller, &5),
+        Err(Ok(Error::NonceAlreadyUsed.into()))
+    );
+    contract.complete_session(&second, &seller, &6);
 }
 
-#[test]
-fn test_authorization_recorded_for_approve() {
-    let (env, contract, token_client, asset_client, buyer, seller, _, _) = setup();
-
-    mint_and_approve(&asset_client, &buyer, 1_000);
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
-    contract.approve_session(&session_id);
-
-    let snapshot = env.to_snapshot();
-    let approve_auth = snapshot.auth.0.last().unwrap();
-    let auth_debug = std::format!("{:?}", approve_auth);
-    assert!(auth_debug.contains("approve_session"));
-}
+// ============================================================================
+// Test: Auto-refund
+// ============================================================================
 
 #[test]
-fn refund_session_buyer_can_refund_before_completion() {
-    let (env, contract, token_client, _, buyer, seller, treasury, contract_id) = setup();
-
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
+fn auto_refund_returns_amount_and_fee_after_dispute_window() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"auto_refund");
 
-    // Verify initial state
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Pending));
-    assert_eq!(token_client.balance(&buyer), 0);
-    assert_eq!(token_client.balance(&contract_id), 1_000);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
 
-    // Buyer refunds the session
-    contract.refund_session(&session_id);
+    assert_eq!(
+        contract.try_auto_refund(&session_id),
+        Err(Ok(Error::DisputeWindowNotElapsed))
+    );
 
-    // Verify session is refunded
-    let refunded_session = contract.get_session(&session_id);
-    assert!(matches!(refunded_session.status, SessionStatus::Approved));
+    advance_ledgers(&env, DEFAULT_DISPUTE_WINDOW_LEDGERS + 1);
+    contract.auto_refund(&session_id);
 
-    // Verify full amount returned to buyer, no fee deducted
-    assert_eq!(token_client.balance(&buyer), 1_000);
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 0);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.status, SessionStatus::Refunded);
+    assert_eq!(token_client.balance(&buyer), 10_000);
+    assert_eq!(token_client.balance(&contract.address), 0);
 }
 
 #[test]
-fn refund_session_full_amount_no_fee() {
-    let (env, contract, token_client, _, buyer, seller, treasury, contract_id) = setup();
-
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &2_500);
+fn session_cannot_be_approved_after_auto_refund() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"auto_then_approve");
 
-    // Buyer refunds
-    contract.refund_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    advance_ledgers(&env, DEFAULT_DISPUTE_WINDOW_LEDGERS + 1);
+    contract.auto_refund(&session_id);
 
-    // Verify full amount refunded (no 5% fee applied)
-    assert_eq!(token_client.balance(&buyer), 2_500);
-    assert_eq!(token_client.balance(&treasury), 0);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(
+        contract.try_approve_session(&session_id, &buyer, &1),
+        Err(Ok(Error::InvalidSessionStatus))
+    );
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Refunded
+    );
+    assert_eq!(token_client.balance(&seller), 0);
 }
 
 #[test]
-fn refund_session_reverts_if_already_completed() {
-    let (env, contract, token_client, _, buyer, seller, _, _) = setup();
+fn auto_refund_requires_completed_session() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"auto_locked");
 
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    advance_ledgers(&env, DEFAULT_DISPUTE_WINDOW_LEDGERS + 1);
 
-    // Try to refund completed session - should fail
-    let result = env.try_invoke_contract(
-        &contract.address,
-        &"refund_session",
-        session_id.into_val(&env),
+    assert_eq!(
+        contract.try_auto_refund(&session_id),
+        Err(Ok(Error::InvalidSessionStatus))
     );
-
-    assert!(result.is_err());
-
-    // Verify error message contains expected panic
-    let error = result.unwrap_err();
-    let error_str = std::format!("{:?}", error);
-    assert!(error_str.contains("refund only allowed for pending sessions"));
 }
 
 #[test]
-fn refund_session_reverts_if_already_approved() {
-    let (env, contract, token_client, _, buyer, seller, _, _) = setup();
-
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
-    contract.approve_session(&session_id);
+fn auto_refund_emits_event() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"auto_event");
 
-    // Try to refund approved session - should fail
-    let result = env.try_invoke_contract(
-        &contract.address,
-        &"refund_session",
-        session_id.into_val(&env),
-    );
-
-    assert!(result.is_err());
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    advance_ledgers(&env, DEFAULT_DISPUTE_WINDOW_LEDGERS + 1);
+    contract.auto_refund(&session_id);
 
-    // Verify error message contains expected panic
-    let error = result.unwrap_err();
-    let error_str = std::format!("{:?}", error);
-    assert!(error_str.contains("refund only allowed for pending sessions"));
+    let data = find_event(&env, "AutoRefundExecuted").unwrap();
+    let data = AutoRefundExecutedEvent::try_from_val(&env, &data).unwrap();
+    assert_eq!(data.session_id, session_id);
+    assert_eq!(data.buyer, buyer);
+    assert_eq!(data.amount, 1_050);
 }
 
-#[test]
-fn refund_session_emits_session_refunded_event() {
-    let (env, contract, token_client, _, buyer, seller, treasury, contract_id) = setup();
-
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-
-    // Buyer refunds
-    contract.refund_session(&session_id);
+// ============================================================================
+// Test: Dispute Window Configuration
+// ============================================================================
 
-    // Verify SessionRefunded event was emitted
-    let events = env.events().all();
-    assert_eq!(events.len(), 2); // create_session and refund_session events
+#[test]
+fn set_dispute_window_updates_value_and_emits_event() {
+    let (env, contract, _token, _admin, _treasury, _buyer, _seller) = setup_escrow();
 
-    let refund_event = &events[1];
-    assert_eq!(refund_event.0, contract_id);
-    assert!(std::format!("{:?}", refund_event.1).contains("refunded"));
+    contract.set_dispute_window(&2_000);
+    assert_eq!(contract.get_dispute_window(), 2_000);
 
-    // Verify event data contains session_id
-    let event_topics = refund_event.1.clone();
-    assert_eq!(event_topics.topics.get(1), Some(&session_id.into_val(&env)));
+    assert!(find_event(&env, "DisputeWindowUpdated").is_some());
 }
 
 #[test]
-fn refund_session_requires_buyer_authorization() {
-    let (env, contract, token_client, _, buyer, seller, _, _) = setup();
-
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-
-    // Try to refund as seller (not buyer) - should fail due to auth
-    env.mock_auths(&[]);
-    env.mock_auths(&[
-        &seller, // Only seller authorized, not buyer
-    ]);
+fn set_dispute_window_rejects_too_small() {
+    let (_env, contract, _token, _admin, _treasury, _buyer, _seller) = setup_escrow();
 
-    let result = env.try_invoke_contract(
-        &contract.address,
-        &"refund_session",
-        session_id.into_val(&env),
+    assert_eq!(
+        contract.try_set_dispute_window(&(DISPUTE_WINDOW_MIN_LEDGERS - 1)),
+        Err(Ok(Error::InvalidDisputeWindow))
     );
-
-    assert!(result.is_err());
+    assert_eq!(contract.get_dispute_window(), DEFAULT_DISPUTE_WINDOW_LEDGERS);
 }
 
 #[test]
-fn refund_session_after_refund_cannot_refund_again() {
-    let (env, contract, token_client, _, buyer, seller, _, _) = setup();
-
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
+fn set_dispute_window_rejects_too_large() {
+    let (_env, contract, _token, _admin, _treasury, _buyer, _seller) = setup_escrow();
 
-    // First refund succeeds
-    contract.refund_session(&session_id);
-
-    // Try to refund again - should fail
-    let result = env.try_invoke_contract(
-        &contract.address,
-        &"refund_session",
-        session_id.into_val(&env),
+    assert_eq!(
+        contract.try_set_dispute_window(&(DISPUTE_WINDOW_MAX_LEDGERS + 1)),
+        Err(Ok(Error::InvalidDisputeWindow))
     );
-
-    assert!(result.is_err());
-
-    // Verify error message
-    let error = result.unwrap_err();
-    let error_str = std::format!("{:?}", error);
-    assert!(error_str.contains("refund only allowed for pending sessions"));
+    assert_eq!(contract.get_dispute_window(), DEFAULT_DISPUTE_WINDOW_LEDGERS);
 }
 
 #[test]
-fn lock_funds_stores_locked_session() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let contract_id = env.register_contract(None, CoreContract);
-    let client = CoreContractClient::new(&env, &contract_id);
+fn set_dispute_window_accepts_minimum_value() {
+    let (_env, contract, _token, _admin, _treasury, _buyer, _seller) = setup_escrow();
 
-    let seller = Address::generate(&env);
-    let session_id = lock_session_id(&env);
-
-    client.lock_funds(&session_id, &seller, &500_i128);
-
-    let session = client.get_locked_session(&session_id).unwrap();
-    assert_eq!(session.seller, seller);
-    assert_eq!(session.amount, 500_i128);
-    assert_eq!(session.status, SessionStatus::Locked);
+    contract.set_dispute_window(&DISPUTE_WINDOW_MIN_LEDGERS);
+    assert_eq!(contract.get_dispute_window(), DISPUTE_WINDOW_MIN_LEDGERS);
 }
 
 #[test]
-#[should_panic]
-fn lock_funds_rejects_duplicate_session_ids() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let contract_id = env.register_contract(None, CoreContract);
-    let client = CoreContractClient::new(&env, &contract_id);
-
-    let seller = Address::generate(&env);
-    let session_id = lock_session_id(&env);
+fn set_dispute_window_accepts_maximum_value() {
+    let (_env, contract, _token, _admin, _treasury, _buyer, _seller) = setup_escrow();
 
-    client.lock_funds(&session_id, &seller, &500_i128);
-    client.lock_funds(&session_id, &seller, &500_i128);
+    contract.set_dispute_window(&DISPUTE_WINDOW_MAX_LEDGERS);
+    assert_eq!(contract.get_dispute_window(), DISPUTE_WINDOW_MAX_LEDGERS);
 }
 
 #[test]
-#[should_panic]
-fn lock_funds_requires_positive_amount() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let contract_id = env.register_contract(None, CoreContract);
-    let client = CoreContractClient::new(&env, &contract_id);
-
-    let seller = Address::generate(&env);
-
-    client.lock_funds(&lock_session_id(&env), &seller, &0_i128);
+fn set_dispute_window_requires_admin_auth() {
+    let (env, contract, _token, admin, _treasury, _buyer, _seller) = setup_escrow();
 
-    use crate::{CoreContract, CoreContractClient, SessionStatus};
-    use soroban_sdk::{
-        testutils::{Address as _, Ledger as _, LedgerInfo},
-        token, vec, Address, Bytes, Env, IntoVal, Symbol,
-    };
-
-fn setup_env() -> (Env, SkillSyncContractClient, Address, Address) {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register_contract(None, SkillSyncContract);
-    let client = SkillSyncContractClient::new(&env, &contract_id);
-    let admin = Address::generate(&env);
-    let treasury = Address::generate(&env);
-    client.init(&admin, &100, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS);
-    (env, client, admin, treasury)
+    contract.set_dispute_window(&2_000);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, admin);
 }
 
-    #[test]
-    fn test_auto_refund_success() {
-        let (env, client, _admin, _treasury) = setup_env();
-
-        let payer = Address::generate(&env);
-        let payee = Address::generate(&env);
-
-        // Setup token
-        let token_admin = Address::generate(&env);
-        let token_contract = env.register_stellar_asset_contract(token_admin.clone());
-        let token_id = Address::from_contract_id(&env, &token_contract);
-        let token_client = token::Client::new(&env, &token_id);
-
-        let amount = 1000_i128;
-        let fee_bps = 500u32; // 5%
-        let fee = (amount * fee_bps as i128) / 10000;
-        let total = amount + fee;
+// ============================================================================
+// Test: Session cancellation
+// ============================================================================
 
-        token_client.mint(&payer, &total);
+#[test]
+fn cancel_session_refunds_amount_and_fee_to_buyer() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"cancel_1");
 
-        let session_id = Bytes::from_slice(&env, b"session_123");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(token_client.balance(&buyer), 8_950);
 
-        // 1. Lock funds
-        client.lock_funds(&session_id, &payer, &payee, &token_id, &amount, &fee_bps);
-        assert_eq!(token_client.balance(&payer), 0);
+    contract.cancel_session(&session_id, &buyer);
 
-        // 2. Complete session
-        let nonce = 1u64;
-        client.complete_session(&session_id, &payee, &nonce);
+    assert_eq!(token_client.balance(&buyer), 10_000);
+    assert_eq!(token_client.balance(&contract.address), 0);
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.status, SessionStatus::Cancelled);
+}
 
-        let session = client.get_session(&session_id).unwrap();
-        assert_eq!(session.status, SessionStatus::Completed);
+#[test]
+fn cancel_session_rejects_seller() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"cancel_2");
 
-    // 3. Try auto_refund before window (should fail)
-    let result = client.try_auto_refund(&session_id);
-    assert!(result.is_err());
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    // 4. Advance ledger sequence beyond dispute window
-    env.ledger().set(LedgerInfo {
-        timestamp: env.ledger().timestamp() + 10000, // Advance time as well
-        protocol_version: 20,
-        sequence_number: DEFAULT_DISPUTE_WINDOW_LEDGERS + 100,
-        network_id: [0u8; 32],
-        base_reserve: 100,
-        min_temp_entry_ttl: 1,
-        min_persistent_entry_ttl: 1,
-        max_entry_ttl: 100,
-    });
+    let result = contract.try_cancel_session(&session_id, &seller);
+    assert_eq!(result, Err(Ok(Error::NotAuthorizedParty)));
+}
 
-        // 5. Run auto_refund
-        client.auto_refund(&session_id);
+#[test]
+fn cancel_session_disputed_requires_admin() {
+    let (env, contract, token_client, admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"cancel_3");
 
-        // 6. Verify refund
-        assert_eq!(token_client.balance(&payer), total);
-        let session = client.get_session(&session_id).unwrap();
-        assert_eq!(session.status, SessionStatus::Refunded);
-    }
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.open_dispute(&session_id, &seller, &Bytes::from_slice(&env, b"no show"));
 
-    #[test]
-    fn test_auto_refund_fails_if_not_completed() {
-        let (env, client, _admin, _treasury) = setup_env();
-
-        let payer = Address::generate(&env);
-        let payee = Address::generate(&env);
-        let token_admin = Address::generate(&env);
-        let token_contract = env.register_stellar_asset_contract(token_admin.clone());
-        let token_id = Address::from_contract_id(&env, &token_contract);
-        let token_client = token::Client::new(&env, &token_id);
-
-        let amount = 1000_i128;
-        token_client.mint(&payer, &1100);
-
-        let session_id = Bytes::from_slice(&env, b"session_locked");
-        client.lock_funds(&session_id, &payer, &payee, &token_id, &amount, &0);
-
-        // Advance ledger sequence beyond dispute window
-        env.ledger().set(LedgerInfo {
-            timestamp: env.ledger().timestamp() + 10000, // Advance time as well
-            protocol_version: 20,
-            sequence_number: DEFAULT_DISPUTE_WINDOW_LEDGERS + 100,
-            network_id: [0u8; 32],
-            base_reserve: 100,
-            min_temp_entry_ttl: 1,
-            min_persistent_entry_ttl: 1,
-            max_entry_ttl: 100,
-        });
-
-        // Should fail because status is Locked, not Completed
-        let result = client.try_auto_refund(&session_id);
-        assert!(result.is_err());
-    }
+    let result = contract.try_cancel_session(&session_id, &buyer);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
 
-    #[test]
-    fn test_upgrade() {
-        let env = Env::default();
-        env.mock_all_auths();
+    contract.cancel_session(&session_id, &admin);
+    assert_eq!(token_client.balance(&buyer), 10_000);
+}
 
-        let admin = Address::generate(&env);
-        let treasury = Address::generate(&env);
+#[test]
+fn cancel_session_rejects_approved_session() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"cancel_4");
 
-        let contract_id = env.register_contract(None, SkillSyncContract);
-        let client = SkillSyncContractClient::new(&env, &contract_id);
-        client.init(&admin, &100, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
 
-        let admin = Address::generate(&env);
-        client.init(&admin);
+    let result = contract.try_cancel_session(&session_id, &buyer);
+    assert_eq!(result, Err(Ok(Error::InvalidSessionStatus)));
+}
 
-        let result = client.hello(&symbol_short!("World"));
-        assert_eq!(
-            result,
-            vec![&env, symbol_short!("Hello"), symbol_short!("World")]
-        );
-    }
+#[test]
+fn cancel_session_rejects_completed_session() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"cancel_5");
 
-    #[test]
-    fn test_upgrade() {
-        let env = Env::default();
-        env.mock_all_auths();
-
-        let contract_id = env.register_contract(None, CoreContract);
-        let client = CoreContractClient::new(&env, &contract_id);
-
-        let admin = Address::generate(&env);
-        client.init(&admin);
-
-        let new_wasm_hash = BytesN::from_array(&env, &[1; 32]);
-        client.upgrade(&new_wasm_hash);
-
-        // Auth should be checked
-        assert_eq!(
-            env.auths(),
-            alloc::vec![(
-                admin.clone(),
-                soroban_sdk::testutils::MockAuthInvoke {
-                    contract: &contract_id,
-                    fn_name: "upgrade",
-                    args: vec![&env, new_wasm_hash.clone().into_val(&env)],
-                    sub_invokes: &[],
-                }
-            )]
-        );
-    }
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.initialize(&admin, &treasury, &500);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
 
-    (
-        env,
-        contract,
-        token_client,
-        asset_client,
-        buyer,
-        seller,
-        treasury,
-        admin,
-        contract_id,
-    )
+    let result = contract.try_cancel_session(&session_id, &buyer);
+    assert_eq!(result, Err(Ok(Error::InvalidSessionStatus)));
+    assert_eq!(token_client.balance(&contract.address), 1_050);
 }
 
 #[test]
-fn approve_session_releases_payout_fee_and_event() {
-    let (env, contract, token_client, _, buyer, seller, treasury, _admin, contract_id) = setup();
+fn cancel_session_does_not_refund_imported_session() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let attacker = Address::generate(&env);
+    let session_id = Bytes::from_slice(&env, b"cancel_6");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    let payer = Address::generate(&env);
-    let payee = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let token_contract = env.register_stellar_asset_contract(token_admin.clone());
-    let token_id = Address::from_contract_id(&env, &token_contract);
-    let token_client = token::Client::new(&env, &token_id);
+    let mut copy = contract.get_session(&session_id).unwrap();
+    copy.session_id = Bytes::from_slice(&env, b"cancel_7");
+    copy.payer = attacker.clone();
+    contract.put_session(&copy);
 
-    let amount = 1000_i128;
-    let fee_bps = 250u32; // 2.5%
-    let fee = (amount * fee_bps as i128) / 10000; // 25
-    let total = amount + fee; // 1025
-
-    token_client.mint(&payer, &total);
+    let result = contract.try_cancel_session(&copy.session_id, &attacker);
+    assert_eq!(result, Err(Ok(Error::RefundFailed)));
+    assert_eq!(token_client.balance(&attacker), 0);
+    assert_eq!(token_client.balance(&contract.address), 1_050);
+}
 
-    let session_id = Bytes::from_slice(&env, b"dispute_session");
+// ============================================================================
+// Test: Dispute lifecycle
+// ============================================================================
 
-    // 1. Lock funds
-    client.lock_funds(&session_id, &payer, &payee, &token_id, &amount, &fee_bps);
+#[test]
+fn raise_dispute_within_window_marks_session_disputed() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"dispute_1");
 
-    // 2. Open dispute as payer
-    let reason = Bytes::from_slice(&env, b"Service not as described");
-    client.open_dispute(&session_id, &payer, &reason);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.raise_dispute(&session_id, &buyer);
+    let event = find_event(&env, "DisputeRaised").unwrap();
+    let event = DisputeOpenedEvent::try_from_val(&env, &event).unwrap();
+    assert_eq!(event.opened_by, buyer);
+    let legacy = find_event(&env, "DisputeOpened").unwrap();
+    let legacy = DisputeOpenedEvent::try_from_val(&env, &legacy).unwrap();
+    assert_eq!(legacy.session_id, session_id);
+    assert_eq!(legacy.opened_by, buyer);
 
-    let session = client.get_session(&session_id).unwrap();
+    let session = contract.get_session(&session_id).unwrap();
     assert_eq!(session.status, SessionStatus::Disputed);
-    assert!(session.dispute_opened_at > 0);
-
-    // 3. Resolve dispute (50/50 split)
-    let to_payer = 500_i128;
-    let to_payee = 500_i128;
-    let note = Some(Bytes::from_slice(&env, b"Split agreement"));
-
-    client.resolve_dispute(&session_id, &to_payer, &to_payee, &note);
-
-    // 4. Verify balances
-    assert_eq!(token_client.balance(&payer), to_payer);
-    assert_eq!(token_client.balance(&payee), to_payee);
-    assert_eq!(token_client.balance(&treasury), fee);
-
-    let session = client.get_session(&session_id).unwrap();
-    assert_eq!(session.status, SessionStatus::Resolved);
-    assert_eq!(session.resolved_at, env.ledger().timestamp());
-    assert_eq!(session.resolver, Some(admin));
+    assert_eq!(session.dispute_opened_at, env.ledger().timestamp());
 }
 
 #[test]
-fn approve_session_records_buyer_authorization() {
-    let (env, contract, token_client, _, buyer, seller, _treasury, _admin, _contract_id) = setup();
+fn raise_dispute_rejects_non_party() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"dispute_2");
+    let stranger = Address::generate(&env);
 
-    let session_id = Bytes::from_slice(&env, b"auth_test");
-    client.lock_funds(&session_id, &payer, &payee, &token_id, &1000, &0);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    let unauthorized = Address::generate(&env);
-    let result = client.try_open_dispute(&session_id, &unauthorized, &Bytes::new(&env));
-    assert!(result.is_err());
+    let result = contract.try_raise_dispute(&session_id, &stranger);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
 }
 
 #[test]
-fn test_open_dispute_on_completed_session() {
-    let (env, client, _admin, _treasury) = setup_env();
-    let payer = Address::generate(&env);
-    let payee = Address::generate(&env);
-    let token_contract = env.register_stellar_asset_contract(Address::generate(&env));
-    let token_id = Address::from_contract_id(&env, &token_contract);
-    let token_client = token::Client::new(&env, &token_id);
-
-    token_client.mint(&payer, &1000);
-    let session_id = Bytes::from_slice(&env, b"completed_dispute");
-    client.lock_funds(&session_id, &payer, &payee, &token_id, &1000, &0);
+fn open_dispute_on_completed_session() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"dispute_completed");
 
-    // Mark as completed
-    client.complete_session(&session_id, &payee, &1u64);
-
-    // Open dispute
-    client.open_dispute(
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.open_dispute(
         &session_id,
-        &payee,
+        &seller,
         &Bytes::from_slice(&env, b"Completed but unhappy"),
     );
 
-    let session = client.get_session(&session_id).unwrap();
+    let session = contract.get_session(&session_id).unwrap();
     assert_eq!(session.status, SessionStatus::Disputed);
-    let snapshot = env.to_snapshot();
-    let approve_auth = snapshot.auth.0.last().unwrap();
-    let auth_debug = std::format!("{:?}", approve_auth);
-    assert!(auth_debug.contains("approve_session"));
+    assert_eq!(session.dispute_opened_at, env.ledger().timestamp());
 }
 
 #[test]
-fn auto_refund_works_after_dispute_window_expires() {
-    let (mut env, contract, token_client, _, buyer, seller, _, contract_id) = setup();
-
-    // Create and complete session
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+fn raise_dispute_rejected_after_window() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"dispute_3");
 
-    // Verify session is completed
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Completed));
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
 
-    // Get the dispute window duration (604800 seconds = 7 days)
-    let dispute_window = contract.dispute_window_secs();
-    let completed_at = session.completed_at;
-
-    // Advance time past the dispute window
     env.ledger()
-        .set_timestamp(completed_at + dispute_window + 1);
-
-    // Execute auto-refund
-    contract.auto_refund(&session_id);
+        .with_mut(|li| li.sequence_number += DEFAULT_DISPUTE_WINDOW_LEDGERS + 1);
 
-    // Verify session status is now Refunded
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Refunded));
+    let result = contract.try_raise_dispute(&session_id, &buyer);
+    assert_eq!(
+        result,
+        Err(Ok(TimeoutDisputeError::DisputeWindowElapsed.into()))
+    );
 }
 
 #[test]
-fn auto_refund_does_not_trigger_before_window_expires() {
-    let (mut env, contract, token_client, _, buyer, seller, _, contract_id) = setup();
+fn resolve_dispute_splits_funds_between_parties() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"dispute_4");
 
-    // Create and complete session
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.raise_dispute(&session_id, &seller);
+    contract.resolve_dispute(&session_id, &2, &300, &700);
 
-    // Get the completed timestamp
-    let session = contract.get_session(&session_id);
-    let completed_at = session.completed_at;
-    let dispute_window = contract.dispute_window_secs();
+    assert_eq!(token_client.balance(&buyer), 9_250);
+    assert_eq!(token_client.balance(&seller), 700);
+    assert_eq!(token_client.balance(&treasury), 50);
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.status, SessionStatus::Resolved);
+}
 
-    // Advance time to just before the window expires
-    env.ledger()
-        .set_timestamp(completed_at + dispute_window - 1);
+#[test]
+fn resolve_dispute_full_refund_to_buyer() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"dispute_5");
 
-    // Try to auto-refund - should panic
-    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        contract.auto_refund(&session_id);
-    }));
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.raise_dispute(&session_id, &buyer);
+    contract.resolve_dispute(&session_id, &0, &1_000, &0);
 
-    assert!(
-        result.is_err(),
-        "auto_refund should fail before window expires"
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Resolved
     );
-
-    // Verify session is still completed
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Completed));
+    assert_eq!(token_client.balance(&buyer), 9_950);
+    assert_eq!(token_client.balance(&seller), 0);
+    assert_eq!(token_client.balance(&treasury), 50);
+    assert_eq!(token_client.balance(&contract.address), 0);
 }
 
 #[test]
-fn auto_refund_buyer_receives_full_amount() {
-    let (mut env, contract, token_client, _, buyer, seller, _, contract_id) = setup();
+fn resolve_dispute_zero_buyer_refund() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"dispute_6");
 
-    let amount = 1_000;
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &amount);
-    contract.complete_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.raise_dispute(&session_id, &seller);
+    contract.resolve_dispute(&session_id, &1, &0, &1_000);
 
-    // Record buyer balance before refund
-    let buyer_balance_before = token_client.balance(&buyer);
-    assert_eq!(buyer_balance_before, 0); // Buyer spent the amount
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Resolved
+    );
+    assert_eq!(token_client.balance(&buyer), 8_950);
+    assert_eq!(token_client.balance(&seller), 1_000);
+    assert_eq!(token_client.balance(&treasury), 50);
+    assert_eq!(token_client.balance(&contract.address), 0);
+}
 
-    // Advance time past dispute window
-    let session = contract.get_session(&session_id);
-    let completed_at = session.completed_at;
-    let dispute_window = contract.dispute_window_secs();
-    env.ledger()
-        .set_timestamp(completed_at + dispute_window + 1);
+// ============================================================================
+// Test: Session enumeration
+// ============================================================================
 
-    // Execute auto-refund
-    contract.auto_refund(&session_id);
+#[test]
+fn sessions_are_indexed_by_payer_and_payee() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let other_seller = Address::generate(&env);
+    let first = Bytes::from_slice(&env, b"enum_1");
+    let second = Bytes::from_slice(&env, b"enum_2");
 
-    // Verify buyer received full amount back
-    let buyer_balance_after = token_client.balance(&buyer);
-    assert_eq!(buyer_balance_after, amount);
+    contract.lock_funds(&first, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.lock_funds(&second, &buyer, &other_seller, &token_client.address, &1_000, &None);
 
-    // Verify contract balance is 0
-    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(
+        contract.sessions_by_payer(&buyer, &0, &10),
+        vec![&env, first.clone(), second.clone()]
+    );
+    assert_eq!(contract.sessions_by_payee(&seller, &0, &10), vec![&env, first]);
+    assert_eq!(contract.sessions_by_payee(&other_seller, &0, &10), vec![&env, second]);
 }
 
 #[test]
-fn auto_refund_emits_autorefundexecuted_event() {
-    let (mut env, contract, token_client, _, buyer, seller, _, contract_id) = setup();
+fn sessions_by_payer_paginates() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let ids = [
+        Bytes::from_slice(&env, b"page_1"),
+        Bytes::from_slice(&env, b"page_2"),
+        Bytes::from_slice(&env, b"page_3"),
+    ];
+    for id in ids.iter() {
+        contract.lock_funds(id, &buyer, &seller, &token_client.address, &100, &None);
+    }
 
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+    assert_eq!(
+        contract.sessions_by_payer(&buyer, &0, &2),
+        vec![&env, ids[0].clone(), ids[1].clone()]
+    );
+    assert_eq!(contract.sessions_by_payer(&buyer, &1, &2), vec![&env, ids[2].clone()]);
+    assert_eq!(contract.sessions_by_payer(&buyer, &2, &2).len(), 0);
+}
 
-    // Advance time past dispute window
-    let session = contract.get_session(&session_id);
-    let completed_at = session.completed_at;
-    let dispute_window = contract.dispute_window_secs();
-    env.ledger()
-        .set_timestamp(completed_at + dispute_window + 1);
+#[test]
+fn put_session_does_not_touch_party_index_or_stats() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let locked = Bytes::from_slice(&env, b"enum_3");
+    contract.lock_funds(&locked, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    // Execute auto-refund
-    contract.auto_refund(&session_id);
+    let mut copy = contract.get_session(&locked).unwrap();
+    copy.session_id = Bytes::from_slice(&env, b"enum_4");
+    contract.put_session(&copy);
 
-    // Check for AutoRefundExecuted event
-    let events = env.events().all();
-    let last_event = events.last().unwrap();
+    assert_eq!(contract.sessions_by_payer(&buyer, &0, &10), vec![&env, locked.clone()]);
+    assert_eq!(contract.sessions_by_payee(&seller, &0, &10), vec![&env, locked]);
+    assert_eq!(contract.get_stats().total_sessions, 1);
+    assert_eq!(contract.get_token_volume(&token_client.address), 1_000);
+}
 
-    // Verify event is from our contract
-    assert_eq!(last_event.0, contract_id);
+#[test]
+fn put_session_requires_admin_auth() {
+    let (env, contract, token_client, admin, _treasury, buyer, seller) = setup_escrow();
+    let locked = Bytes::from_slice(&env, b"enum_5");
+    contract.lock_funds(&locked, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    // Verify event topic contains "autorefund"
-    let topics_str = std::format!("{:?}", last_event.1);
-    assert!(topics_str.contains("autorefund"));
+    let mut copy = contract.get_session(&locked).unwrap();
+    copy.session_id = Bytes::from_slice(&env, b"enum_6");
+    contract.put_session(&copy);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, admin);
+}
+
+// ============================================================================
+// Test: Emergency pause
+// ============================================================================
 
-    // Verify event data can be decoded as AutoRefundExecutedEvent
-    let event_data: AutoRefundExecutedEvent = last_event.2.clone().unwrap();
-    assert_eq!(event_data.session_id, session_id);
-    assert_eq!(event_data.amount, 1_000);
+#[test]
+fn pause_blocks_lock_complete_and_approve() {
+    let (env, contract, token_client, admin, _treasury, buyer, seller) = setup_escrow();
+    let locked = Bytes::from_slice(&env, b"pause_1");
+    let completed = Bytes::from_slice(&env, b"pause_2");
+
+    contract.lock_funds(&locked, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.lock_funds(&completed, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&completed, &seller, &1);
+
+    contract.pause(&admin);
+    assert!(contract.is_paused());
+
+    let new_id = Bytes::from_slice(&env, b"pause_3");
+    assert_eq!(
+        contract.try_lock_funds(&new_id, &buyer, &seller, &token_client.address, &1_000, &None),
+        Err(Ok(Error::ContractPaused))
+    );
+    assert_eq!(
+        contract.try_complete_session(&locked, &seller, &2),
+        Err(Ok(Error::ContractPaused.into()))
+    );
+    assert_eq!(
+        contract.try_approve_session(&completed, &buyer, &1),
+        Err(Ok(Error::ContractPaused))
+    );
+
+    contract.unpause(&admin);
+    contract.approve_session(&completed, &buyer, &1);
+    assert_eq!(
+        contract.get_session(&completed).unwrap().status,
+        SessionStatus::Approved
+    );
 }
 
 #[test]
-fn session_cannot_be_approved_after_auto_refund() {
-    let (mut env, contract, token_client, _, buyer, seller, _, _) = setup();
+fn pause_still_allows_reads_and_refunds() {
+    let (env, contract, token_client, admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"pause_4");
 
-    let session_id = contract.create_session(&buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&session_id);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.pause(&admin);
 
-    // Advance time past dispute window
-    let session = contract.get_session(&session_id);
-    let completed_at = session.completed_at;
-    let dispute_window = contract.dispute_window_secs();
-    env.ledger()
-        .set_timestamp(completed_at + dispute_window + 1);
+    assert!(contract.get_session(&session_id).is_some());
+    assert_eq!(contract.get_platform_fee(), 500);
 
-    // Execute auto-refund
-    contract.auto_refund(&session_id);
+    contract.cancel_session(&session_id, &buyer);
+    assert_eq!(token_client.balance(&buyer), 10_000);
+}
 
-    // Try to approve the session - should fail
-    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        contract.approve_session(&session_id);
-    }));
+#[test]
+fn pause_blocks_state_changes_but_allows_view() {
+    let (env, contract, token_client, admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"pause_5");
 
-    assert!(
-        result.is_err(),
-        "approve_session should fail after auto-refund"
+    contract.pause(&admin);
+    assert!(contract.is_paused());
+    assert_eq!(contract.get_platform_fee(), 500);
+    assert_eq!(
+        contract.try_lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None),
+        Err(Ok(Error::ContractPaused))
     );
+    assert!(contract.get_session(&session_id).is_none());
 
-    // Verify session is still in Refunded state
-    let session = contract.get_session(&session_id);
-    assert!(matches!(session.status, SessionStatus::Refunded));
+    contract.unpause(&admin);
+    assert!(!contract.is_paused());
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(token_client.balance(&contract.address), 1_050);
 }
 
+// ============================================================================
+// Test: Token allowlist
+// ============================================================================
+
 #[test]
-fn apply_fee_zero_fee() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, _contract_id) = setup();
-    contract.set_fee_bps(&0);
+fn lock_funds_rejects_token_not_on_allowlist() {
+    let (env, contract, _token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let other_token = env.register_stellar_asset_contract(Address::generate(&env));
+    StellarAssetClient::new(&env, &other_token).mint(&buyer, &10_000);
+    let session_id = Bytes::from_slice(&env, b"allow_1");
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.approve_session(&1);
+    assert!(!contract.is_token_allowed(&other_token));
+    let result = contract.try_lock_funds(&session_id, &buyer, &seller, &other_token, &1_000, &None);
+    assert_eq!(result, Err(Ok(Error::AssetNotAllowed)));
 
-    assert_eq!(token_client.balance(&seller), 1_000);
-    assert_eq!(token_client.balance(&treasury), 0);
+    contract.add_allowed_token(&other_token);
+    assert!(contract.is_token_allowed(&other_token));
+    contract.lock_funds(&session_id, &buyer, &seller, &other_token, &1_000, &None);
 }
 
 #[test]
-fn apply_fee_max_fee() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, _contract_id) = setup();
-    contract.set_fee_bps(&10_000);
+fn remove_allowed_token_blocks_new_escrows_only() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let existing = Bytes::from_slice(&env, b"allow_2");
+    let rejected = Bytes::from_slice(&env, b"allow_3");
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.approve_session(&1);
+    contract.lock_funds(&existing, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.remove_allowed_token(&token_client.address);
+    assert!(!contract.is_token_allowed(&token_client.address));
 
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 1_000);
+    let result =
+        contract.try_lock_funds(&rejected, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(result, Err(Ok(Error::AssetNotAllowed)));
+
+    contract.cancel_session(&existing, &buyer);
+    assert_eq!(token_client.balance(&buyer), 10_000);
 }
 
+// ============================================================================
+// Test: Amount limits
+// ============================================================================
+
 #[test]
-fn apply_fee_rounding() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, _contract_id) = setup();
-    contract.set_fee_bps(&1);
+fn amount_limits_default_to_full_range() {
+    let (_env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    assert_eq!(contract.get_amount_limits(), (1, MAX_AMOUNT));
+}
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &10_000);
-    contract.complete_session(&1);
-    contract.approve_session(&1);
+#[test]
+fn lock_funds_enforces_amount_limits() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    contract.set_amount_limits(&100, &2_000);
+    assert_eq!(contract.get_amount_limits(), (100, 2_000));
 
-    assert_eq!(token_client.balance(&seller), 9_999);
-    assert_eq!(token_client.balance(&treasury), 1);
+    let dust = Bytes::from_slice(&env, b"limit_1");
+    assert_eq!(
+        contract.try_lock_funds(&dust, &buyer, &seller, &token_client.address, &99, &None),
+        Err(Ok(Error::AmountBelowMinimum))
+    );
+
+    let whale = Bytes::from_slice(&env, b"limit_2");
+    assert_eq!(
+        contract.try_lock_funds(&whale, &buyer, &seller, &token_client.address, &2_001, &None),
+        Err(Ok(Error::AmountAboveMaximum))
+    );
+
+    let ok = Bytes::from_slice(&env, b"limit_3");
+    contract.lock_funds(&ok, &buyer, &seller, &token_client.address, &2_000, &None);
+}
+
+#[test]
+fn set_amount_limits_rejects_inverted_range() {
+    let (_env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    assert_eq!(
+        contract.try_set_amount_limits(&500, &100),
+        Err(Ok(Error::InvalidAmount))
+    );
+    assert_eq!(
+        contract.try_set_amount_limits(&0, &100),
+        Err(Ok(Error::InvalidAmount))
+    );
 }
 
+// ============================================================================
+// Test: Platform fee configuration
+// ============================================================================
+
 #[test]
-fn early_refund_skips_fee() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, contract_id) = setup();
+fn lock_funds_defaults_to_stored_platform_fee() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    contract.set_platform_fee(&250);
+    assert_eq!(contract.get_platform_fee(), 250);
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.refund_session(&1);
+    let session_id = Bytes::from_slice(&env, b"fee_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(env.auths()[0].0, buyer);
 
-    let session = contract.get_session(&1);
-    assert!(matches!(session.status, SessionStatus::Refunded));
-    assert_eq!(token_client.balance(&buyer), 1_000);
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 0);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(contract.get_session(&session_id).unwrap().fee_bps, 250);
+    assert_eq!(token_client.balance(&buyer), 8_975);
 }
 
 #[test]
-fn late_refund_applies_fee() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, contract_id) = setup();
+fn lock_funds_honours_explicit_fee_within_bounds() {
+    let (env, contract, token_client, admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"fee_2");
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.refund_session(&1);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &Some(100));
+    assert!(env.auths().iter().any(|(address, _)| *address == admin));
+    assert_eq!(contract.get_session(&session_id).unwrap().fee_bps, 100);
 
-    let session = contract.get_session(&1);
-    assert!(matches!(session.status, SessionStatus::Refunded));
-    assert_eq!(token_client.balance(&buyer), 950);
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 50);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    let too_high = Bytes::from_slice(&env, b"fee_3");
+    assert_eq!(
+        contract.try_lock_funds(
+            &too_high,
+            &buyer,
+            &seller,
+            &token_client.address,
+            &1_000,
+            &Some(PLATFORM_FEE_MAX_BPS + 1)
+        ),
+        Err(Ok(Error::InvalidFeeBps))
+    );
 }
 
 #[test]
-fn resolve_dispute_applies_fee_and_splits() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, contract_id) = setup();
+fn set_platform_fee_rejects_out_of_bounds() {
+    let (_env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    assert_eq!(
+        contract.try_set_platform_fee(&(PLATFORM_FEE_MAX_BPS + 1)),
+        Err(Ok(Error::InvalidFeeBps))
+    );
+    assert_eq!(contract.get_platform_fee(), 500);
+}
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.resolve_dispute(&1, &400);
+// ============================================================================
+// Test: Session metadata hash
+// ============================================================================
 
-    let session = contract.get_session(&1);
-    assert!(matches!(session.status, SessionStatus::Resolved));
-    assert_eq!(token_client.balance(&buyer), 400);
-    assert_eq!(token_client.balance(&seller), 550);
-    assert_eq!(token_client.balance(&treasury), 50);
-    assert_eq!(token_client.balance(&contract_id), 0);
+fn legacy_session_v1(env: &Env, session_id: &Bytes, payer: &Address, payee: &Address, asset: &Address) -> SessionV1 {
+    SessionV1 {
+        version: 1,
+        session_id: session_id.clone(),
+        payer: payer.clone(),
+        payee: payee.clone(),
+        asset: asset.clone(),
+        amount: 1_000,
+        fee_bps: 500,
+        status: SessionStatus::Locked,
+        created_at: env.ledger().timestamp(),
+        updated_at: env.ledger().timestamp(),
+        dispute_deadline: (env.ledger().sequence() + DEFAULT_DISPUTE_WINDOW_LEDGERS) as u64,
+        expires_at: env.ledger().timestamp() + ESCROW_DURATION_SECONDS,
+        payer_approved: false,
+        payee_approved: false,
+        approved_at: 0,
+        dispute_opened_at: 0,
+        resolved_at: 0,
+        resolver: None,
+        resolution_note: None,
+        deadline: (env.ledger().sequence() + DEFAULT_MAX_SESSION_DURATION_LEDGERS) as u64,
+        pending_extension: None,
+    }
 }
 
 #[test]
-fn resolve_dispute_full_refund_to_buyer() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, contract_id) = setup();
+fn set_session_metadata_by_either_party() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"meta_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.resolve_dispute(&1, &950);
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.version, SESSION_VERSION);
+    assert_eq!(session.metadata_hash, None);
 
-    let session = contract.get_session(&1);
-    assert!(matches!(session.status, SessionStatus::Resolved));
-    assert_eq!(token_client.balance(&buyer), 950);
-    assert_eq!(token_client.balance(&seller), 0);
-    assert_eq!(token_client.balance(&treasury), 50);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    let agenda = BytesN::from_array(&env, &[7; 32]);
+    contract.set_session_metadata(&session_id, &buyer, &agenda);
+    assert_eq!(contract.get_session(&session_id).unwrap().metadata_hash, Some(agenda));
+
+    let terms = BytesN::from_array(&env, &[9; 32]);
+    contract.set_session_metadata(&session_id, &seller, &terms);
+    assert_eq!(contract.get_session(&session_id).unwrap().metadata_hash, Some(terms));
 }
 
 #[test]
-fn resolve_dispute_zero_buyer_refund() {
-    let (_env, contract, token_client, _, buyer, seller, treasury, _admin, contract_id) = setup();
+fn set_session_metadata_rejects_stranger_and_approved_sessions() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"meta_2");
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.resolve_dispute(&1, &0);
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        contract.try_set_session_metadata(&session_id, &stranger, &hash),
+        Err(Ok(Error::NotAuthorizedParty))
+    );
 
-    let session = contract.get_session(&1);
-    assert!(matches!(session.status, SessionStatus::Resolved));
-    assert_eq!(token_client.balance(&buyer), 0);
-    assert_eq!(token_client.balance(&seller), 950);
-    assert_eq!(token_client.balance(&treasury), 50);
-    assert_eq!(token_client.balance(&contract_id), 0);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
+    assert_eq!(
+        contract.try_set_session_metadata(&session_id, &buyer, &hash),
+        Err(Ok(Error::InvalidSessionStatus))
+    );
 }
 
 #[test]
-fn initialize_sets_admin_treasury_and_fee_bps() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let admin = Address::generate(&env);
-    let treasury = Address::generate(&env);
+fn version_one_sessions_are_readable_and_accept_metadata() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"meta_v1");
+    let legacy = legacy_session_v1(&env, &session_id, &buyer, &seller, &token_client.address);
+    env.as_contract(&contract.address, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Session(session_id.clone()), &legacy);
+    });
 
-    let contract_id = env.register_contract(None, CoreContract);
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.initialize(&admin, &treasury, &500);
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.version, 1);
+    assert_eq!(session.amount, 1_000);
+    assert_eq!(session.metadata_hash, None);
 
-    assert_eq!(contract.admin(), admin);
-    assert_eq!(contract.treasury(), treasury);
-    assert_eq!(contract.fee_bps(), 500);
+    let hash = BytesN::from_array(&env, &[3; 32]);
+    contract.set_session_metadata(&session_id, &seller, &hash);
+    assert_eq!(contract.get_session(&session_id).unwrap().metadata_hash, Some(hash));
+}
+
+// ============================================================================
+// Test: Session migration
+// ============================================================================
+
+fn store_legacy_session(env: &Env, contract: &SkillSyncContractClient, session: &SessionV1) {
+    env.as_contract(&contract.address, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Session(session.session_id.clone()), session);
+    });
 }
 
 #[test]
-#[should_panic(expected = "contract already initialized")]
-fn initialize_twice_panics() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let admin = Address::generate(&env);
-    let treasury = Address::generate(&env);
+fn migrate_session_upgrades_legacy_record() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"migrate_1");
+    let mut legacy = legacy_session_v1(&env, &session_id, &buyer, &seller, &token_client.address);
+    legacy.version = 0;
+    store_legacy_session(&env, &contract, &legacy);
+
+    assert!(contract.migrate_session(&session_id));
+
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.version, SESSION_VERSION);
+    assert_eq!(session.metadata_hash, None);
+    assert_eq!(session.amount, legacy.amount);
+    assert_eq!(session.payer, buyer);
+    assert_eq!(session.payee, seller);
 
-    let contract_id = env.register_contract(None, CoreContract);
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.initialize(&admin, &treasury, &500);
-    contract.initialize(&admin, &treasury, &500);
+    // Already current: nothing to do
+    assert!(!contract.migrate_session(&session_id));
 }
 
 #[test]
-fn initialize_emits_event() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let admin = Address::generate(&env);
-    let treasury = Address::generate(&env);
+fn migrate_session_unknown_id() {
+    let (env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    let result = contract.try_migrate_session(&Bytes::from_slice(&env, b"missing"));
+    assert_eq!(result, Err(Ok(Error::SessionNotFound)));
+}
+
+#[test]
+fn migrate_sessions_batch_skips_current_sessions() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
 
-    let contract_id = env.register_contract(None, CoreContract);
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.initialize(&admin, &treasury, &500);
+    let old_a = Bytes::from_slice(&env, b"migrate_a");
+    let old_b = Bytes::from_slice(&env, b"migrate_b");
+    let current = Bytes::from_slice(&env, b"migrate_c");
+    store_legacy_session(
+        &env,
+        &contract,
+        &legacy_session_v1(&env, &old_a, &buyer, &seller, &token_client.address),
+    );
+    store_legacy_session(
+        &env,
+        &contract,
+        &legacy_session_v1(&env, &old_b, &buyer, &seller, &token_client.address),
+    );
+    contract.lock_funds(&current, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    let events = env.events().all();
-    let init_event = events
-        .iter()
-        .find(|e| std::format!("{:?}", e.1).contains("init"))
-        .unwrap();
-    assert_eq!(init_event.0, contract_id);
+    let mut ids = Vec::new(&env);
+    ids.push_back(old_a.clone());
+    ids.push_back(old_b.clone());
+    ids.push_back(current.clone());
+    assert_eq!(contract.migrate_sessions_batch(&ids), 2);
+
+    for id in ids.iter() {
+        assert_eq!(contract.get_session(&id).unwrap().version, SESSION_VERSION);
+    }
+
+    // Migrated sessions keep working with version 2 features
+    let hash = BytesN::from_array(&env, &[5; 32]);
+    contract.set_session_metadata(&old_a, &buyer, &hash);
+    assert_eq!(contract.get_session(&old_a).unwrap().metadata_hash, Some(hash));
+}
+
+// ============================================================================
+// Test: Contract upgrade
+// ============================================================================
+
+#[test]
+fn set_upgrade_timelock_validates_duration() {
+    let (_env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    assert_eq!(contract.get_upgrade_timelock(), 0);
+
+    let result = contract.try_set_upgrade_timelock(&(MIN_UPGRADE_TIMELOCK_SECONDS - 1));
+    assert_eq!(result, Err(Ok(Error::InvalidTimelock)));
+
+    contract.set_upgrade_timelock(&DEFAULT_UPGRADE_TIMELOCK_SECONDS);
+    assert_eq!(contract.get_upgrade_timelock(), DEFAULT_UPGRADE_TIMELOCK_SECONDS);
+
+    let shorter = DEFAULT_UPGRADE_TIMELOCK_SECONDS - 1;
+    assert_eq!(
+        contract.try_set_upgrade_timelock(&shorter),
+        Err(Ok(Error::InvalidTimelock))
+    );
+    assert_eq!(contract.try_set_upgrade_timelock(&0), Err(Ok(Error::InvalidTimelock)));
+
+    contract.set_upgrade_timelock(&(DEFAULT_UPGRADE_TIMELOCK_SECONDS * 2));
+    assert_eq!(contract.get_upgrade_timelock(), DEFAULT_UPGRADE_TIMELOCK_SECONDS * 2);
 }
 
 #[test]
-#[should_panic(expected = "contract not initialized")]
-fn uninitialized_lock_funds_reverts() {
+fn propose_and_cancel_upgrade() {
+    let (env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    contract.set_upgrade_timelock(&DEFAULT_UPGRADE_TIMELOCK_SECONDS);
+
+    let hash = BytesN::from_array(&env, &[4; 32]);
+    let pending = contract.propose_upgrade(&hash);
+    assert_eq!(pending.new_wasm_hash, Bytes::from_array(&env, &[4; 32]));
+    assert_eq!(
+        pending.deadline,
+        env.ledger().timestamp() + DEFAULT_UPGRADE_TIMELOCK_SECONDS
+    );
+    assert_eq!(contract.get_pending_upgrade(), Some(pending));
+
+    contract.cancel_upgrade();
+    assert_eq!(contract.get_pending_upgrade(), None);
+    assert_eq!(contract.try_cancel_upgrade(), Err(Ok(Error::UpgradeNotProposed)));
+}
+
+#[test]
+fn upgrade_enforces_proposal_and_timelock() {
+    let (env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    contract.set_upgrade_timelock(&DEFAULT_UPGRADE_TIMELOCK_SECONDS);
+
+    let hash = BytesN::from_array(&env, &[6; 32]);
+    assert_eq!(contract.try_upgrade(&hash), Err(Ok(Error::UpgradeNotProposed)));
+
+    contract.propose_upgrade(&hash);
+    assert_eq!(contract.try_upgrade(&hash), Err(Ok(Error::UpgradeNotReady)));
+
+    let other = BytesN::from_array(&env, &[8; 32]);
+    assert_eq!(contract.try_upgrade(&other), Err(Ok(Error::UpgradeNotProposed)));
+}
+
+#[test]
+fn raising_timelock_delays_pending_proposal() {
+    let (env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    let hash = BytesN::from_array(&env, &[9; 32]);
+
+    // Proposed with no timelock, so the upgrade could be applied at once
+    let pending = contract.propose_upgrade(&hash);
+    assert_eq!(pending.deadline, 0);
+
+    contract.set_upgrade_timelock(&DEFAULT_UPGRADE_TIMELOCK_SECONDS);
+    assert_eq!(
+        contract.get_pending_upgrade().unwrap().deadline,
+        pending.proposed_at + DEFAULT_UPGRADE_TIMELOCK_SECONDS
+    );
+    assert_eq!(contract.try_upgrade(&hash), Err(Ok(Error::UpgradeNotReady)));
+
+    contract.set_upgrade_timelock(&(DEFAULT_UPGRADE_TIMELOCK_SECONDS * 2));
+    assert_eq!(
+        contract.get_pending_upgrade().unwrap().deadline,
+        pending.proposed_at + DEFAULT_UPGRADE_TIMELOCK_SECONDS * 2
+    );
+}
+
+#[test]
+fn upgrade_requires_admin_auth() {
+    let (env, contract, _token_client, admin, _treasury, _buyer, _seller) = setup_escrow();
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    contract.set_upgrade_timelock(&DEFAULT_UPGRADE_TIMELOCK_SECONDS);
+
+    contract.propose_upgrade(&hash);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(contract.try_upgrade(&hash), Err(Ok(Error::UpgradeNotReady)));
+}
+
+// ============================================================================
+// Test: Dispute deadline extension
+// ============================================================================
+
+#[test]
+fn party_extends_dispute_deadline() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"extend_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    let original = contract.get_session(&session_id).unwrap().dispute_deadline;
+
+    let new_deadline = contract.extend_dispute_deadline(&session_id, &buyer, &500);
+    assert_eq!(new_deadline, original + 500);
+
+    contract.extend_dispute_deadline(&session_id, &seller, &250);
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().dispute_deadline,
+        original + 750
+    );
+
+    // The extended window still accepts disputes
+    env.ledger()
+        .with_mut(|li| li.sequence_number = (original + 100) as u32);
+    contract.raise_dispute(&session_id, &seller);
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Disputed
+    );
+}
+
+#[test]
+fn party_extensions_are_capped() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"extend_2");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+
+    contract.extend_dispute_deadline(&session_id, &buyer, &(MAX_DISPUTE_EXTENSION_LEDGERS - 10));
+    assert_eq!(
+        contract.try_extend_dispute_deadline(&session_id, &seller, &11),
+        Err(Ok(Error::InvalidExtensionDuration.into()))
+    );
+    assert_eq!(
+        contract.try_extend_dispute_deadline(&session_id, &seller, &0),
+        Err(Ok(Error::InvalidExtensionDuration.into()))
+    );
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        contract.try_extend_dispute_deadline(&session_id, &stranger, &1),
+        Err(Ok(Error::NotAuthorizedParty.into()))
+    );
+}
+
+#[test]
+fn admin_extends_dispute_deadline_after_window() {
+    let (env, contract, token_client, admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"extend_3");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    let original = contract.get_session(&session_id).unwrap().dispute_deadline;
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number = (original + 1) as u32);
+    assert_eq!(
+        contract.try_extend_dispute_deadline(&session_id, &buyer, &100),
+        Err(Ok(TimeoutDisputeError::DisputeWindowElapsed.into()))
+    );
+
+    let extra = MAX_DISPUTE_EXTENSION_LEDGERS + 1_000;
+    assert_eq!(
+        contract.extend_dispute_deadline(&session_id, &admin, &extra),
+        original + extra
+    );
+}
+
+// ============================================================================
+// Test: Native XLM escrow
+// ============================================================================
+
+/// Instance key the Stellar Asset Contract stores its admin under. The native
+/// asset has no issuer, so tests install a minter here to fund accounts.
+#[contracttype]
+enum NativeAssetKey {
+    Admin,
+}
+
+fn setup_native_escrow() -> (
+    Env,
+    SkillSyncContractClient<'static>,
+    TokenClient<'static>,
+    Address,
+    Address,
+    Address,
+    Address,
+) {
     let env = Env::default();
     env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
     let buyer = Address::generate(&env);
     let seller = Address::generate(&env);
 
-    let contract_id = env.register_contract(None, CoreContract);
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.lock_funds(&1, &buyer, &seller, &buyer, &100);
+    let native = env
+        .deployer()
+        .with_stellar_asset(Bytes::from_array(&env, &[0u8; 4]))
+        .deploy();
+    let minter = Address::generate(&env);
+    env.as_contract(&native, || {
+        env.storage().instance().set(&NativeAssetKey::Admin, &minter);
+    });
+    StellarAssetClient::new(&env, &native).mint(&buyer, &10_000);
+
+    let contract_id = env.register_contract(None, SkillSyncContract);
+    let contract = SkillSyncContractClient::new(&env, &contract_id);
+    contract.init(&admin, &500, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS);
+    assert_eq!(contract.get_native_asset(), native);
+    contract.add_allowed_token(&native);
+
+    let token_client = TokenClient::new(&env, &native);
+    (env, contract, token_client, admin, treasury, buyer, seller)
 }
 
 #[test]
-#[should_panic(expected = "contract not initialized")]
-fn uninitialized_get_session_reverts() {
-    let env = Env::default();
-    let contract_id = env.register_contract(None, CoreContract);
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.get_session(&1);
+fn native_lock_complete_and_approve() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_native_escrow();
+    let session_id = Bytes::from_slice(&env, b"native_1");
+
+    contract.lock_funds_native(&session_id, &buyer, &seller, &1_000, &None);
+    assert_eq!(token_client.balance(&buyer), 8_950);
+    assert_eq!(token_client.balance(&contract.address), 1_050);
+
+    let session = contract.get_session(&session_id).unwrap();
+    assert_eq!(session.asset, token_client.address);
+
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
+    assert_eq!(token_client.balance(&seller), 1_000);
+    assert_eq!(token_client.balance(&treasury), 50);
 }
 
 #[test]
-#[should_panic(expected = "contract not initialized")]
-fn uninitialized_admin_reverts() {
-    let env = Env::default();
-    let contract_id = env.register_contract(None, CoreContract);
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.admin();
+fn native_lock_and_refund() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_native_escrow();
+    let session_id = Bytes::from_slice(&env, b"native_2");
+
+    contract.lock_funds_native(&session_id, &buyer, &seller, &1_000, &None);
+    contract.cancel_session(&session_id, &buyer);
+
+    assert_eq!(token_client.balance(&buyer), 10_000);
+    assert_eq!(token_client.balance(&contract.address), 0);
 }
 
 #[test]
-fn happy_path_lock_complete_approve_payout_and_events() {
-    let (env, contract, token_client, _, buyer, seller, treasury, _admin, contract_id) = setup();
+fn native_lock_requires_allowlisting() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_native_escrow();
+    contract.remove_allowed_token(&token_client.address);
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    assert!(matches!(
-        contract.get_session(&1).status,
-        SessionStatus::Pending
-    ));
+    let session_id = Bytes::from_slice(&env, b"native_3");
+    let result = contract.try_lock_funds_native(&session_id, &buyer, &seller, &1_000, &None);
+    assert_eq!(result, Err(Ok(Error::AssetNotAllowed)));
+}
 
-    contract.complete_session(&1);
-    assert!(matches!(
-        contract.get_session(&1).status,
-        SessionStatus::Completed
-    ));
+// ============================================================================
+// Test: Session statistics
+// ============================================================================
 
-    contract.approve_session(&1);
-    assert!(matches!(
-        contract.get_session(&1).status,
-        SessionStatus::Approved
-    ));
+#[test]
+fn stats_track_lock_complete_and_cancel() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    assert_eq!(contract.get_stats(), SessionStats::default());
+
+    let first = Bytes::from_slice(&env, b"stats_1");
+    let second = Bytes::from_slice(&env, b"stats_2");
+    contract.lock_funds(&first, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.lock_funds(&second, &buyer, &seller, &token_client.address, &2_000, &None);
+
+    contract.complete_session(&first, &seller, &1);
+    contract.cancel_session(&second, &buyer);
+
+    assert_eq!(
+        contract.get_stats(),
+        SessionStats {
+            total_sessions: 2,
+            total_completed: 1,
+            total_cancelled: 1,
+        }
+    );
+    assert_eq!(contract.get_token_volume(&token_client.address), 3_000);
+}
+
+#[test]
+fn token_volume_is_tracked_per_asset() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let other_asset = env.register_stellar_asset_contract(Address::generate(&env));
+    contract.add_allowed_token(&other_asset);
+    StellarAssetClient::new(&env, &other_asset).mint(&buyer, &10_000);
+
+    contract.lock_funds(
+        &Bytes::from_slice(&env, b"vol_1"),
+        &buyer,
+        &seller,
+        &token_client.address,
+        &1_500,
+        &None,
+    );
+    contract.lock_funds(
+        &Bytes::from_slice(&env, b"vol_2"),
+        &buyer,
+        &seller,
+        &other_asset,
+        &400,
+        &None,
+    );
+
+    assert_eq!(contract.get_token_volume(&token_client.address), 1_500);
+    assert_eq!(contract.get_token_volume(&other_asset), 400);
+    assert_eq!(contract.get_stats().total_sessions, 2);
+}
+
+// ============================================================================
+// Test: Approval operators
+// ============================================================================
+
+#[test]
+fn operator_approves_on_behalf_of_buyer() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let operator = Address::generate(&env);
+    let session_id = Bytes::from_slice(&env, b"operator_1");
 
-    assert_eq!(token_client.balance(&buyer), 0);
-    assert_eq!(token_client.balance(&seller), 950);
+    contract.set_operator(&buyer, &operator);
+    assert_eq!(contract.get_operator(&buyer), Some(operator.clone()));
+
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &operator, &1);
+
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Approved
+    );
+    assert_eq!(token_client.balance(&seller), 1_000);
     assert_eq!(token_client.balance(&treasury), 50);
-    assert_eq!(token_client.balance(&contract_id), 0);
+}
 
-    let events = env.events().all();
-    let topics: Vec<String> = events.iter().map(|e| std::format!("{:?}", e.1)).collect();
-    let completed_idx = topics.iter().position(|t| t.contains("completed")).unwrap();
-    let fee_idx = topics
-        .iter()
-        .position(|t| t.contains("fee_deducted"))
-        .unwrap();
-    let approved_idx = topics.iter().position(|t| t.contains("approved")).unwrap();
+#[test]
+fn revoked_or_foreign_operator_cannot_approve() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let operator = Address::generate(&env);
+    let session_id = Bytes::from_slice(&env, b"operator_2");
 
-    assert!(
-        completed_idx < fee_idx,
-        "completed event should come before fee_deducted"
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+
+    // An operator of the seller cannot approve the buyer's release
+    contract.set_operator(&seller, &operator);
+    assert_eq!(
+        contract.try_approve_session(&session_id, &operator, &1),
+        Err(Ok(Error::NotAuthorizedParty))
     );
-    assert!(
-        fee_idx < approved_idx,
-        "fee_deducted event should come before approved"
+
+    contract.set_operator(&buyer, &operator);
+    contract.revoke_operator(&buyer);
+    assert_eq!(contract.get_operator(&buyer), None);
+    assert_eq!(
+        contract.try_approve_session(&session_id, &operator, &2),
+        Err(Ok(Error::NotAuthorizedParty))
     );
 }
 
 #[test]
-#[should_panic(expected = "session not found")]
-fn seller_cannot_complete_without_lock() {
-    let (_, contract, _, _, _, _, _, _, _) = setup();
-    contract.complete_session(&999);
+fn replacing_operator_revokes_previous_one() {
+    let (env, contract, _token_client, _admin, _treasury, buyer, _seller) = setup_escrow();
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+
+    contract.set_operator(&buyer, &first);
+    contract.set_operator(&buyer, &first);
+    assert!(find_event(&env, "OperatorRevoked").is_none());
+
+    contract.set_operator(&buyer, &second);
+    assert_eq!(contract.get_operator(&buyer), Some(second));
+
+    let data = find_event(&env, "OperatorRevoked").expect("OperatorRevoked not emitted");
+    let event = OperatorRevokedEvent::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.party, buyer);
+    assert_eq!(event.operator, first);
 }
 
 #[test]
-#[should_panic(expected = "session must be pending")]
-fn seller_cannot_complete_twice() {
-    let (_, contract, token_client, _, buyer, seller, _, _, _) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.complete_session(&1);
+fn set_operator_rejects_self() {
+    let (_env, contract, _token_client, _admin, _treasury, buyer, _seller) = setup_escrow();
+    assert_eq!(
+        contract.try_set_operator(&buyer, &buyer),
+        Err(Ok(Error::InvalidAddress))
+    );
 }
 
+// ============================================================================
+// Test: Payee fee overrides
+// ============================================================================
+
 #[test]
-#[should_panic(expected = "session must be completed")]
-fn buyer_cannot_approve_before_completion() {
-    let (_, contract, token_client, _, buyer, seller, _, _, _) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.approve_session(&1);
+fn fee_override_discounts_new_sessions() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    contract.set_fee_override(&seller, &100);
+    assert_eq!(contract.get_fee_override(&seller), Some(100));
+    assert_eq!(contract.get_payee_fee(&seller), 100);
+
+    let session_id = Bytes::from_slice(&env, b"discount_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(contract.get_session(&session_id).unwrap().fee_bps, 100);
+    assert_eq!(token_client.balance(&buyer), 8_990);
+
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
+    assert_eq!(token_client.balance(&seller), 1_000);
+    assert_eq!(token_client.balance(&treasury), 10);
 }
 
 #[test]
-fn platform_fee_deducted_correctly() {
-    let (_, contract, token_client, _, buyer, seller, treasury, _, _) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.approve_session(&1);
-    assert_eq!(token_client.balance(&treasury), 50);
+fn fee_waiver_and_clear() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    contract.set_fee_override(&seller, &0);
+
+    let waived = Bytes::from_slice(&env, b"waived");
+    contract.lock_funds(&waived, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(contract.get_session(&waived).unwrap().fee_bps, 0);
+
+    contract.clear_fee_override(&seller);
+    assert_eq!(contract.get_fee_override(&seller), None);
+    assert_eq!(contract.get_payee_fee(&seller), 500);
+
+    let regular = Bytes::from_slice(&env, b"regular");
+    contract.lock_funds(&regular, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert_eq!(contract.get_session(&regular).unwrap().fee_bps, 500);
+    // Existing sessions keep the fee they were locked with
+    assert_eq!(contract.get_session(&waived).unwrap().fee_bps, 0);
 }
 
 #[test]
-fn seller_receives_correct_payout() {
-    let (_, contract, token_client, _, buyer, seller, _, _, _) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.complete_session(&1);
-    contract.approve_session(&1);
-    assert_eq!(token_client.balance(&seller), 950);
+fn fee_override_is_bounded() {
+    let (_env, contract, _token_client, _admin, _treasury, _buyer, seller) = setup_escrow();
+    assert_eq!(
+        contract.try_set_fee_override(&seller, &(PLATFORM_FEE_MAX_BPS + 1)),
+        Err(Ok(Error::InvalidFeeBps))
+    );
 }
 
-// TASK 4: lock_funds tests
+// ============================================================================
+// Test: Versioned event payloads
+// ============================================================================
+
+#[test]
+fn funds_locked_event_decodes_as_struct() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"event_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+
+    let (contract_id, _topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract_id, contract.address);
+    let event = FundsLocked::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.session_id, session_id);
+    assert_eq!(event.payer, buyer);
+    assert_eq!(event.payee, seller);
+    assert_eq!(event.asset, token_client.address);
+    assert_eq!(event.amount, 1_000);
+    assert_eq!(event.fee, 50);
+}
 
 #[test]
-fn lock_funds_with_sufficient_balance() {
-    let (_, contract, token_client, _, buyer, seller, _, _, contract_id) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
+fn session_completed_event_decodes_as_struct() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"event_2");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+
+    let (_contract_id, _topics, data) = env.events().all().last().unwrap();
+    let event = SessionCompleted::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.session_id, session_id);
+    assert_eq!(event.payee, seller);
+    assert_eq!(event.amount, 1_000);
+}
+
+// ============================================================================
+// Test: Checks-effects-interactions
+// ============================================================================
+
+/// Token that, on every transfer, reads the watched session straight from
+/// the escrow's storage and records the status it finds, so tests can check
+/// that state was written before the escrow called out.
+#[contract]
+struct ObservingToken;
+
+#[contracttype]
+enum ObservingTokenKey {
+    Watch,
+    Transfers,
+    Received(Address),
+    Seen(Address),
+}
+
+#[contractimpl]
+impl ObservingToken {
+    pub fn watch(env: Env, escrow: Address, session_id: Bytes) {
+        env.storage()
+            .instance()
+            .set(&ObservingTokenKey::Watch, &(escrow, session_id));
+    }
+
+    pub fn balance(_env: Env, _id: Address) -> i128 {
+        1_000_000
+    }
+
+    pub fn transfer(env: Env, _from: Address, to: Address, amount: i128) {
+        let transfers: u32 = env
+            .storage()
+            .instance()
+            .get(&ObservingTokenKey::Transfers)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&ObservingTokenKey::Transfers, &(transfers + 1));
+
+        let key = ObservingTokenKey::Received(to.clone());
+        let received: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(received + amount));
+
+        let (escrow, session_id): (Address, Bytes) =
+            env.storage().instance().get(&ObservingTokenKey::Watch).unwrap();
+        let status = env.as_contract(&escrow, || load_session(&env, &session_id).map(|s| s.status));
+        if let Some(status) = status {
+            env.storage()
+                .instance()
+                .set(&ObservingTokenKey::Seen(to), &status);
+        }
+    }
+
+    pub fn transfers(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ObservingTokenKey::Transfers)
+            .unwrap_or(0)
+    }
+
+    pub fn received(env: Env, id: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ObservingTokenKey::Received(id))
+            .unwrap_or(0)
+    }
 
-    let session = contract.get_session(&1);
-    assert!(matches!(session.status, SessionStatus::Pending));
-    assert_eq!(token_client.balance(&contract_id), 1_000);
-    assert_eq!(token_client.balance(&buyer), 0);
+    /// Session status observed during the last transfer to `id`.
+    pub fn seen(env: Env, id: Address) -> Option<SessionStatus> {
+        env.storage().instance().get(&ObservingTokenKey::Seen(id))
+    }
+}
+
+fn setup_observed_session(
+    session_id: &[u8],
+) -> (
+    Env,
+    SkillSyncContractClient<'static>,
+    ObservingTokenClient<'static>,
+    Address,
+    Address,
+    Address,
+    Bytes,
+) {
+    let (env, contract, _token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let token_id = env.register_contract(None, ObservingToken);
+    let token = ObservingTokenClient::new(&env, &token_id);
+    contract.add_allowed_token(&token_id);
+
+    let session_id = Bytes::from_slice(&env, session_id);
+    token.watch(&contract.address, &session_id);
+    (env, contract, token, treasury, buyer, seller, session_id)
 }
 
 #[test]
-fn contract_balance_increases_by_exact_amount() {
-    let (_, contract, token_client, _, buyer, seller, _, _, contract_id) = setup();
-    let before = token_client.balance(&contract_id);
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    let after = token_client.balance(&contract_id);
-    assert_eq!(after - before, 1_000);
+fn lock_funds_stores_session_before_pulling_funds() {
+    let (_env, contract, token, _treasury, buyer, seller, session_id) =
+        setup_observed_session(b"cei_lock");
+
+    contract.lock_funds(&session_id, &buyer, &seller, &token.address, &1_000, &None);
+
+    assert_eq!(token.received(&contract.address), 1_050);
+    assert_eq!(token.seen(&contract.address), Some(SessionStatus::Locked));
 }
 
 #[test]
-#[should_panic(expected = "session already exists")]
-fn duplicate_session_id_reverts() {
-    let (_, contract, token_client, _, buyer, seller, _, _, _) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &2_000);
+fn complete_session_makes_no_token_calls() {
+    let (_env, contract, token, _treasury, buyer, seller, session_id) =
+        setup_observed_session(b"cei_complete");
+    contract.lock_funds(&session_id, &buyer, &seller, &token.address, &1_000, &None);
+    let transfers = token.transfers();
+
+    contract.complete_session(&session_id, &seller, &1);
+
+    assert_eq!(token.transfers(), transfers);
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().status,
+        SessionStatus::Completed
+    );
 }
 
 #[test]
-#[should_panic(expected = "amount must be positive")]
-fn zero_amount_reverts() {
-    let (_, contract, token_client, _, buyer, seller, _, _, _) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &0);
+fn approve_session_records_approval_before_payout() {
+    let (_env, contract, token, treasury, buyer, seller, session_id) =
+        setup_observed_session(b"cei_approve");
+    contract.lock_funds(&session_id, &buyer, &seller, &token.address, &1_000, &None);
+    contract.complete_session(&session_id, &seller, &1);
+
+    contract.approve_session(&session_id, &buyer, &1);
+
+    assert_eq!(token.seen(&seller), Some(SessionStatus::Approved));
+    assert_eq!(token.seen(&treasury), Some(SessionStatus::Approved));
+    assert_eq!(token.received(&seller), 1_000);
+
+    // The session is already settled, so a second approval pays nothing
+    assert_eq!(
+        contract.try_approve_session(&session_id, &buyer, &2),
+        Err(Ok(Error::InvalidSessionStatus))
+    );
+    assert_eq!(token.received(&seller), 1_000);
 }
 
 #[test]
-#[should_panic]
-fn insufficient_buyer_balance_reverts() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let buyer = Address::generate(&env);
-    let seller = Address::generate(&env);
-    let treasury = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let token_admin = Address::generate(&env);
+fn cancel_session_records_cancellation_before_refund() {
+    let (_env, contract, token, _treasury, buyer, seller, session_id) =
+        setup_observed_session(b"cei_cancel");
+    contract.lock_funds(&session_id, &buyer, &seller, &token.address, &1_000, &None);
 
-    let token_address = env.register_stellar_asset_contract(token_admin.clone());
-    let token_client = TokenClient::new(&env, &token_address);
-    let asset_client = StellarAssetClient::new(&env, &token_address);
+    contract.cancel_session(&session_id, &buyer);
+
+    assert_eq!(token.seen(&buyer), Some(SessionStatus::Cancelled));
+    assert_eq!(token.received(&buyer), 1_050);
+    assert_eq!(
+        contract.try_cancel_session(&session_id, &buyer),
+        Err(Ok(Error::InvalidSessionStatus))
+    );
+    assert_eq!(token.received(&buyer), 1_050);
+}
+
+// ============================================================================
+// Test: Completion receipts
+// ============================================================================
 
-    asset_client.mint(&buyer, &500);
+#[test]
+fn completion_receipt_recorded_on_approval() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"receipt_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
 
-    let contract_id = env.register_contract(None, CoreContract);
-    let contract = CoreContractClient::new(&env, &contract_id);
-    contract.initialize(&admin, &treasury, &500);
+    contract.complete_session(&session_id, &seller, &1);
+    assert_eq!(
+        contract.try_get_completion_receipt(&session_id),
+        Err(Ok(Error::SessionNotApproved))
+    );
 
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
+    contract.approve_session(&session_id, &buyer, &1);
+    let receipt = contract.get_completion_receipt(&session_id);
+    assert_eq!(receipt.session_id, session_id);
+    assert_eq!(receipt.payee, seller);
+    assert_eq!(receipt.asset, token_client.address);
+    assert_eq!(receipt.paid_amount, 1_000);
+    assert_eq!(receipt.fee, 50);
+    assert_eq!(receipt.treasury, treasury);
+    assert_eq!(
+        receipt.completed_at,
+        contract.get_session(&session_id).unwrap().approved_at
+    );
+    assert_eq!(
+        receipt.legs,
+        vec![&env, PayoutLeg { payee: seller, amount: 1_000 }]
+    );
+    assert_eq!(token_client.balance(&contract.address), 0);
 }
 
 #[test]
-fn funds_locked_event_emitted() {
-    let (env, contract, token_client, _, buyer, seller, _, _, contract_id) = setup();
-    contract.lock_funds(&1, &buyer, &seller, &token_client.address, &1_000);
+fn approval_pays_only_unreleased_milestones() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"receipt_2");
+    let milestones = vec![
+        &env,
+        (4_000_u32, Bytes::from_slice(&env, b"outline")),
+        (6_000_u32, Bytes::from_slice(&env, b"delivery")),
+    ];
+    contract.lock_funds_with_milestones(
+        &session_id,
+        &buyer,
+        &seller,
+        &token_client.address,
+        &1_000,
+        &milestones,
+    );
+    contract.release_milestone(&session_id, &0);
+    assert_eq!(token_client.balance(&seller), 400);
+
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
 
-    let events = env.events().all();
-    let locked_event = events
-        .iter()
-        .find(|e| std::format!("{:?}", e.1).contains("locked"))
-        .unwrap();
-    assert_eq!(locked_event.0, contract_id);
+    assert_eq!(contract.get_completion_receipt(&session_id).paid_amount, 600);
+    assert_eq!(token_client.balance(&seller), 1_000);
+    assert_eq!(token_client.balance(&treasury), 50);
+    assert_eq!(token_client.balance(&contract.address), 0);
+}
+
+#[test]
+fn completion_receipt_unknown_session() {
+    let (env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    assert_eq!(
+        contract.try_get_completion_receipt(&Bytes::from_slice(&env, b"missing")),
+        Err(Ok(Error::SessionNotFound))
+    );
+}
+
+#[test]
+fn repeat_complete_session_reports_already_completed() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"receipt_2");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+
+    contract.complete_session(&session_id, &seller, &1);
+    assert_eq!(
+        contract.try_complete_session(&session_id, &seller, &2),
+        Err(Ok(SessionError::SessionAlreadyCompleted.into()))
+    );
+
+    contract.approve_session(&session_id, &buyer, &1);
+    assert_eq!(
+        contract.try_complete_session(&session_id, &seller, &3),
+        Err(Ok(SessionError::SessionAlreadyCompleted.into()))
+    );
 }
 
 // ============================================================================
-// Test: Dispute Window Configuration
+// Test: Session packages
 // ============================================================================
 
+use crate::packages::{
+    PackageCancelledEvent, PackageCreatedEvent, PackageStatus, PackageTrancheReleasedEvent,
+    MAX_PACKAGE_SESSIONS,
+};
+
 #[test]
-fn test_get_dispute_window_returns_default() {
-    let (env, contract, _, _, _, _, _, _, _) = setup_with_admin();
-    
-    // Should return default value of 1000 ledgers
-    let window = contract.get_dispute_window();
-    assert_eq!(window, DEFAULT_DISPUTE_WINDOW_LEDGERS);
-}
-
-#[test]
-fn test_set_dispute_window_updates_value() {
-    let (env, contract, _, _, _, _, _, _, admin) = setup_with_admin();
-    
-    // Set new dispute window
-    let new_window: u32 = 2000;
-    contract.set_dispute_window(&new_window);
-    
-    // Verify it was updated
-    let window = contract.get_dispute_window();
-    assert_eq!(window, new_window);
-}
-
-#[test]
-fn test_set_dispute_window_emits_event() {
-    let (env, contract, _, _, _, _, _, _, admin) = setup_with_admin();
-    
-    let old_window = contract.get_dispute_window();
-    let new_window: u32 = 1500;
-    
-    contract.set_dispute_window(&new_window);
-    
-    // Check that DisputeWindowUpdated event was emitted
-    let events = env.events().all();
-    let event = events.iter().find(|e| {
-        std::format!("{:?}", e.1).contains("DisputeWindowUpdated")
-    });
-    
-    assert!(event.is_some(), "DisputeWindowUpdated event should be emitted");
+fn package_releases_one_tranche_per_confirmation() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+
+    let package_id = contract.create_package(&buyer, &seller, &token_client.address, &1_000, &4);
+    let event = PackageCreatedEvent::try_from_val(&env, &find_event(&env, "pkg_new").unwrap()).unwrap();
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.total_locked, 4_200);
+    // 4 × (1_000 + 5% fee)
+    assert_eq!(token_client.balance(&buyer), 10_000 - 4_200);
+    assert_eq!(token_client.balance(&contract.address), 4_200);
+
+    contract.confirm_package_session(&package_id, &buyer);
+    let event =
+        PackageTrancheReleasedEvent::try_from_val(&env, &find_event(&env, "pkg_paid").unwrap())
+            .unwrap();
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.session_number, 1);
+    contract.confirm_package_session(&package_id, &buyer);
+    assert_eq!(token_client.balance(&seller), 2_000);
+    assert_eq!(token_client.balance(&treasury), 100);
+
+    let package = contract.get_package(&package_id).unwrap();
+    assert_eq!(package.released_sessions, 2);
+    assert_eq!(package.status, PackageStatus::Active);
+
+    contract.confirm_package_session(&package_id, &buyer);
+    contract.confirm_package_session(&package_id, &buyer);
+    let package = contract.get_package(&package_id).unwrap();
+    assert_eq!(package.status, PackageStatus::Completed);
+    assert_eq!(token_client.balance(&seller), 4_000);
+    assert_eq!(token_client.balance(&contract.address), 0);
+
+    assert_eq!(
+        contract.try_confirm_package_session(&package_id, &buyer),
+        Err(Ok(PackageError::PackageNotActive.into()))
+    );
 }
 
 #[test]
-#[should_panic(expected = "InvalidDisputeWindow")]
-fn test_set_dispute_window_rejects_too_small() {
-    let (env, contract, _, _, _, _, _, _, admin) = setup_with_admin();
-    
-    // Try to set window below minimum (10 ledgers)
-    let invalid_window: u32 = 5;
-    contract.set_dispute_window(&invalid_window);
+fn cancel_package_refunds_remaining_tranches() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let package_id = contract.create_package(&buyer, &seller, &token_client.address, &1_000, &3);
+
+    contract.confirm_package_session(&package_id, &buyer);
+    assert_eq!(
+        contract.try_cancel_package(&package_id, &seller),
+        Err(Ok(Error::NotAuthorizedParty.into()))
+    );
+
+    let refund = contract.cancel_package(&package_id, &buyer);
+    assert_eq!(refund, 2_100);
+    let event = PackageCancelledEvent::try_from_val(&env, &find_event(&env, "pkg_end").unwrap()).unwrap();
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.refunded_sessions, 2);
+    assert_eq!(token_client.balance(&buyer), 10_000 - 1_050);
+    assert_eq!(token_client.balance(&contract.address), 0);
+    assert_eq!(
+        contract.get_package(&package_id).unwrap().status,
+        PackageStatus::Cancelled
+    );
+
+    assert_eq!(
+        contract.try_confirm_package_session(&package_id, &buyer),
+        Err(Ok(PackageError::PackageNotActive.into()))
+    );
 }
 
 #[test]
-#[should_panic(expected = "InvalidDisputeWindow")]
-fn test_set_dispute_window_rejects_too_large() {
-    let (env, contract, _, _, _, _, _, _, admin) = setup_with_admin();
-    
-    // Try to set window above maximum (100,000 ledgers)
-    let invalid_window: u32 = 150_000;
-    contract.set_dispute_window(&invalid_window);
+fn create_package_validates_size() {
+    let (_env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    assert_eq!(
+        contract.try_create_package(&buyer, &seller, &token_client.address, &100, &0),
+        Err(Ok(PackageError::InvalidPackageSize.into()))
+    );
+    assert_eq!(
+        contract.try_create_package(
+            &buyer,
+            &seller,
+            &token_client.address,
+            &100,
+            &(MAX_PACKAGE_SESSIONS + 1)
+        ),
+        Err(Ok(PackageError::InvalidPackageSize.into()))
+    );
+    assert_eq!(
+        contract.try_create_package(&buyer, &seller, &token_client.address, &5_000, &2),
+        Err(Ok(Error::InsufficientBalance.into()))
+    );
 }
 
 #[test]
-#[should_panic(expected = "Unauthorized")]
-fn test_set_dispute_window_requires_admin() {
-    let (env, contract, _, _, buyer, _, _, _, admin) = setup_with_admin();
-    
-    // Try to set dispute window as non-admin (should fail)
-    env.mock_all_auths_allowing_non_root_auth();
-    let new_window: u32 = 2000;
-    
-    // This should panic because buyer is not admin
-    contract.set_dispute_window(&new_window);
+fn unknown_package_is_rejected() {
+    let (_env, contract, _token_client, _admin, _treasury, buyer, _seller) = setup_escrow();
+    assert_eq!(
+        contract.try_confirm_package_session(&7, &buyer),
+        Err(Ok(PackageError::PackageNotFound.into()))
+    );
+    assert_eq!(
+        contract.try_cancel_package(&7, &buyer),
+        Err(Ok(PackageError::PackageNotFound.into()))
+    );
+}
+
+// ============================================================================
+// Test: Split payouts
+// ============================================================================
+
+fn split(payee: &Address, bps: u32) -> PayoutSplit {
+    PayoutSplit {
+        payee: payee.clone(),
+        bps,
+    }
+}
+
+#[test]
+fn approval_pays_each_split_leg() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let co_mentor = Address::generate(&env);
+    let session_id = Bytes::from_slice(&env, b"split_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+
+    let splits = vec![&env, split(&seller, 7_000), split(&co_mentor, 3_000)];
+    contract.set_payout_splits(&session_id, &seller, &splits);
+    assert_eq!(contract.get_payout_splits(&session_id), splits);
+    assert_eq!(contract.get_session(&session_id).unwrap().payout_splits, splits);
+
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
+
+    // The full 1_000 is split 70/30; the fee was escrowed on top
+    assert_eq!(token_client.balance(&seller), 700);
+    assert_eq!(token_client.balance(&co_mentor), 300);
+    assert_eq!(token_client.balance(&treasury), 50);
+
+    let receipt = contract.get_completion_receipt(&session_id);
+    assert_eq!(receipt.paid_amount, 1_000);
+    assert_eq!(
+        receipt.legs,
+        vec![
+            &env,
+            PayoutLeg { payee: seller, amount: 700 },
+            PayoutLeg { payee: co_mentor, amount: 300 },
+        ]
+    );
 }
 
 #[test]
-fn test_set_dispute_window_accepts_minimum_value() {
-    let (env, contract, _, _, _, _, _, _, admin) = setup_with_admin();
-    
-    // Set to minimum allowed value
-    let min_window: u32 = DISPUTE_WINDOW_MIN_LEDGERS;
-    contract.set_dispute_window(&min_window);
-    
-    let window = contract.get_dispute_window();
-    assert_eq!(window, min_window);
+fn single_payee_approval_emits_leg_event() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"split_5");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+    assert!(contract.get_session(&session_id).unwrap().payout_splits.is_empty());
+
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
+
+    let data = find_event(&env, "PayoutLegPaid").expect("PayoutLegPaid not emitted");
+    let event = PayoutLegPaid::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.session_id, session_id);
+    assert_eq!(event.payee, seller);
+    assert_eq!(event.amount, 1_000);
 }
 
 #[test]
-fn test_set_dispute_window_accepts_maximum_value() {
-    let (env, contract, _, _, _, _, _, _, admin) = setup_with_admin();
-    
-    // Set to maximum allowed value
-    let max_window: u32 = DISPUTE_WINDOW_MAX_LEDGERS;
-    contract.set_dispute_window(&max_window);
-    
-    let window = contract.get_dispute_window();
-    assert_eq!(window, max_window);
+fn dispute_resolution_pays_seller_share_across_splits() {
+    let (env, contract, token_client, _admin, treasury, buyer, seller) = setup_escrow();
+    let co_mentor = Address::generate(&env);
+    let session_id = Bytes::from_slice(&env, b"split_4");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+
+    let splits = vec![&env, split(&seller, 7_000), split(&co_mentor, 3_000)];
+    contract.set_payout_splits(&session_id, &seller, &splits);
+    contract.raise_dispute(&session_id, &buyer);
+    contract.resolve_dispute(&session_id, &2, &400, &600);
+
+    assert_eq!(token_client.balance(&buyer), 9_350);
+    assert_eq!(token_client.balance(&seller), 420);
+    assert_eq!(token_client.balance(&co_mentor), 180);
+    assert_eq!(token_client.balance(&treasury), 50);
 }
 
 #[test]
-fn test_dispute_window_persists_across_calls() {
-    let (env, contract, _, _, _, _, _, _, admin) = setup_with_admin();
-    
-    // Set dispute window
-    let new_window: u32 = 3000;
-    contract.set_dispute_window(&new_window);
-    
-    // Verify it persists
-    let window1 = contract.get_dispute_window();
-    let window2 = contract.get_dispute_window();
-    
-    assert_eq!(window1, new_window);
-    assert_eq!(window2, new_window);
+fn last_split_leg_absorbs_rounding() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let second = Address::generate(&env);
+    let third = Address::generate(&env);
+    let session_id = Bytes::from_slice(&env, b"split_2");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &Some(0));
+
+    let splits = vec![
+        &env,
+        split(&seller, 3_333),
+        split(&second, 3_333),
+        split(&third, 3_334),
+    ];
+    contract.set_payout_splits(&session_id, &seller, &splits);
+    contract.complete_session(&session_id, &seller, &1);
+    contract.approve_session(&session_id, &buyer, &1);
+
+    assert_eq!(token_client.balance(&seller), 333);
+    assert_eq!(token_client.balance(&second), 333);
+    assert_eq!(token_client.balance(&third), 334);
 }
+
+#[test]
+fn set_payout_splits_validation() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let other = Address::generate(&env);
+    let session_id = Bytes::from_slice(&env, b"split_3");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+
+    let short = vec![&env, split(&seller, 5_000), split(&other, 4_000)];
+    assert_eq!(
+        contract.try_set_payout_splits(&session_id, &seller, &short),
+        Err(Ok(Error::InvalidAmount))
+    );
+
+    let duplicate = vec![&env, split(&seller, 5_000), split(&seller, 5_000)];
+    assert_eq!(
+        contract.try_set_payout_splits(&session_id, &seller, &duplicate),
+        Err(Ok(Error::InvalidAddress))
+    );
+
+    let valid = vec![&env, split(&seller, 5_000), split(&other, 5_000)];
+    assert_eq!(
+        contract.try_set_payout_splits(&session_id, &buyer, &valid),
+        Err(Ok(Error::NotAuthorizedParty))
+    );
+
+    contract.complete_session(&session_id, &seller, &1);
+    assert_eq!(
+        contract.try_set_payout_splits(&session_id, &seller, &valid),
+        Err(Ok(Error::InvalidSessionStatus))
+    );
+}
+
+// ============================================================================
+// Test: Hashed session keys
+// ============================================================================
+
+#[test]
+fn sessions_are_stored_under_hashed_key() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"hashed_1");
+    contract.lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None);
+
+    let key = contract.derive_session_key(&session_id);
+    assert_eq!(key, env.crypto().sha256(&session_id));
+
+    env.as_contract(&contract.address, || {
+        let storage = env.storage().persistent();
+        assert!(storage.has(&DataKey::SessionByKey(key.clone())));
+        assert!(!storage.has(&DataKey::Session(session_id.clone())));
+    });
+}
+
+#[test]
+fn legacy_keyed_session_is_moved_by_migration() {
+    let (env, contract, token_client, _admin, _treasury, buyer, seller) = setup_escrow();
+    let session_id = Bytes::from_slice(&env, b"hashed_2");
+    let mut legacy = legacy_session_v1(&env, &session_id, &buyer, &seller, &token_client.address);
+    legacy.version = SESSION_VERSION;
+    store_legacy_session(&env, &contract, &legacy);
+
+    // Readable through the fallback, and a duplicate id is still rejected
+    assert!(contract.get_session(&session_id).is_some());
+    assert_eq!(
+        contract.try_lock_funds(&session_id, &buyer, &seller, &token_client.address, &1_000, &None),
+        Err(Ok(Error::DuplicateSessionId))
+    );
+
+    // An ordinary write lands on the hashed key and leaves the legacy entry
+    let metadata_hash = BytesN::from_array(&env, &[7; 32]);
+    contract.set_session_metadata(&session_id, &buyer, &metadata_hash);
+    env.as_contract(&contract.address, || {
+        assert!(env
+            .storage()
+            .persistent()
+            .has(&DataKey::Session(session_id.clone())));
+    });
+    assert_eq!(
+        contract.get_session(&session_id).unwrap().metadata_hash,
+        Some(metadata_hash)
+    );
+
+    assert!(contract.migrate_session(&session_id));
+    let key = contract.derive_session_key(&session_id);
+    env.as_contract(&contract.address, || {
+        let storage = env.storage().persistent();
+        assert!(storage.has(&DataKey::SessionByKey(key.clone())));
+        assert!(!storage.has(&DataKey::Session(session_id.clone())));
+    });
+    assert_eq!(contract.get_session(&session_id).unwrap().amount, 1_000);
+}
+
+// ============================================================================
+// Test: Role-based access control
+// ============================================================================
+
+#[test]
+fn pauser_role_can_pause_and_unpause() {
+    let (env, contract, _token_client, admin, _treasury, buyer, _seller) = setup_escrow();
+    let pauser = Address::generate(&env);
+    let pauser_role = crate::rbac::roles::pauser(&env);
+
+    assert_eq!(contract.try_pause(&pauser), Err(Ok(Error::Unauthorized)));
+
+    contract.grant_role(&pauser_role, &pauser);
+    assert!(contract.has_role(&pauser_role, &pauser));
+
+    contract.pause(&pauser);
+    assert!(contract.is_paused());
+    contract.unpause(&pauser);
+    assert!(!contract.is_paused());
+
+    // The pauser role does not extend to other roles.
+    assert_eq!(
+        contract.try_set_treasury(&pauser, &buyer),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    contract.revoke_role(&pauser_role, &pauser);
+    assert!(!contract.has_role(&pauser_role, &pauser));
+    assert_eq!(contract.try_pause(&pauser), Err(Ok(Error::Unauthorized)));
+
+    // The admin passes every role check without an explicit grant.
+    contract.pause(&admin);
+    assert!(contract.is_paused());
+}
+
+#[test]
+fn treasurer_role_can_set_treasury() {
+    let (env, contract, _token_client, _admin, _treasury, _buyer, _seller) = setup_escrow();
+    let treasurer = Address::generate(&env);
+    let new_treasury = Address::generate(&env);
+
+    contract.grant_role(&crate::rbac::roles::treasurer(&env), &treasurer);
+    contract.set_treasury(&treasurer, &new_treasury);
+
+    assert_eq!(contract.get_treasury(), new_treasury);
+}
+
@@ -1,10 +1,11 @@
 #![cfg(test)]
 
 use super::*;
-use crate::DEFAULT_DISPUTE_WINDOW_SECONDS;
-use soroban_sdk::{testutils::Address as _, Bytes, Env};
-
-extern crate std;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    token::{Client as TokenClient, StellarAssetClient},
+    Address, Bytes, Env,
+};
 
 // ============================================================================
 // Storage Persistence Test Suite
@@ -14,52 +15,91 @@ extern crate std;
 // ✓ Lock funds → upgrade contract → session data still readable
 // ✓ Upgrade → treasury and fee config preserved
 // ✓ Multiple sessions with different statuses persist
+//
+// Native tests cannot install new Wasm, so `upgrade_in_place` re-registers
+// the contract code at the same address. Like a real upgrade it replaces
+// only the code and leaves instance and persistent storage untouched.
 // ============================================================================
 
-/// Test 1: Lock funds → upgrade contract → session data still readable
-///
-/// Verifies that session data stored before upgrade remains accessible after
-/// contract upgrade, with all session fields (amount, parties, status) intact.
-#[test]
-fn test_storage_persistence_lock_funds_after_upgrade() {
+fn upgrade_in_place(env: &Env, contract_id: &Address) -> SkillSyncContractClient<'static> {
+    env.register_contract(contract_id, SkillSyncContract);
+    SkillSyncContractClient::new(env, contract_id)
+}
+
+fn setup_contract_with_config() -> (
+    Env,
+    SkillSyncContractClient<'static>,
+    TokenClient<'static>,
+    StellarAssetClient<'static>,
+    Address,
+    Address,
+    Address,
+) {
     let env = Env::default();
     env.mock_all_auths();
 
-    let admin = soroban_sdk::Address::generate(&env);
-    let treasury = soroban_sdk::Address::generate(&env);
-    let payer = soroban_sdk::Address::generate(&env);
-    let payee = soroban_sdk::Address::generate(&env);
-    let asset = soroban_sdk::Address::generate(&env);
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let payer = Address::generate(&env);
+    let payee = Address::generate(&env);
+
+    let token_address = env.register_stellar_asset_contract(Address::generate(&env));
+    let token_client = TokenClient::new(&env, &token_address);
+    let asset_client = StellarAssetClient::new(&env, &token_address);
 
-    // Register and initialize contract
     let contract_id = env.register_contract(None, SkillSyncContract);
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "init"),
-        soroban_sdk::vec![
-            &env,
-            admin.into_val(&env),
-            (500u32).into_val(&env),
-            treasury.into_val(&env),
-            (DEFAULT_DISPUTE_WINDOW_SECONDS).into_val(&env),
-        ],
-    );
-
-    // Create and store a session (Locked status)
-    let session_id = Bytes::from_slice(&env, b"storage_test_lock_001");
-    let session = Session {
-        version: 1,
+    let contract = SkillSyncContractClient::new(&env, &contract_id);
+    contract.init(&admin, &500, &treasury, &DEFAULT_DISPUTE_WINDOW_LEDGERS);
+    contract.add_allowed_token(&token_address);
+
+    (
+        env,
+        contract,
+        token_client,
+        asset_client,
+        treasury,
+        payer,
+        payee,
+    )
+}
+
+fn create_and_lock_session(
+    env: &Env,
+    contract: &SkillSyncContractClient,
+    token_client: &TokenClient,
+    asset_client: &StellarAssetClient,
+    payer: &Address,
+    payee: &Address,
+    amount: i128,
+) -> Bytes {
+    let session_id = Bytes::from_slice(env, b"persisted_session");
+    asset_client.mint(payer, &(amount + amount * 500 / 10_000));
+    contract.lock_funds(
+        &session_id,
+        payer,
+        payee,
+        &token_client.address,
+        &amount,
+        &None,
+    );
+    session_id
+}
+
+fn stored_session(env: &Env, session_id: &Bytes, status: SessionStatus, amount: i128) -> Session {
+    let now = env.ledger().timestamp();
+    Session {
+        version: SESSION_VERSION,
         session_id: session_id.clone(),
-        payer: payer.clone(),
-        payee: payee.clone(),
-        asset: asset.clone(),
-        amount: 5000,
+        payer: Address::generate(env),
+        payee: Address::generate(env),
+        asset: Address::generate(env),
+        amount,
         fee_bps: 500,
-        status: SessionStatus::Locked,
-        created_at: env.ledger().timestamp(),
-        updated_at: env.ledger().timestamp(),
-        dispute_deadline: env.ledger().timestamp() + DEFAULT_DISPUTE_WINDOW_SECONDS,
-        expires_at: env.ledger().timestamp() + 7 * 24 * 60 * 60,
+        status,
+        created_at: now,
+        updated_at: now,
+        dispute_deadline: env.ledger().sequence() as u64 + DEFAULT_DISPUTE_WINDOW_LEDGERS as u64,
+        expires_at: now + ESCROW_DURATION_SECONDS,
         payer_approved: false,
         payee_approved: false,
         approved_at: 0,
@@ -67,49 +107,48 @@ fn test_storage_persistence_lock_funds_after_upgrade() {
         resolved_at: 0,
         resolver: None,
         resolution_note: None,
-    };
+        deadline: env.ledger().sequence() as u64 + DEFAULT_DISPUTE_WINDOW_LEDGERS as u64,
+        pending_extension: None,
+        metadata_hash: None,
+        payout_splits: Vec::new(env),
+    }
+}
 
-    // Store session before upgrade
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "put_session"),
-        soroban_sdk::vec![&env, session.into_val(&env)],
-    );
+/// Test 1: Lock funds → upgrade contract → session data still readable
+///
+/// Verifies that session data stored before upgrade remains accessible after
+/// contract upgrade, with all session fields (amount, parties, status) intact.
+#[test]
+fn test_storage_persistence_lock_funds_after_upgrade() {
+    let (env, contract, token_client, asset_client, _treasury, payer, payee) =
+        setup_contract_with_config();
 
-    // Verify session exists before upgrade
-    let before: Option<Session> = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_session"),
-        soroban_sdk::vec![&env, session_id.clone().into_val(&env)],
-    );
-    assert!(before.is_some(), "Session must exist before upgrade");
-    assert_eq!(
-        before.unwrap().amount,
+    let session_id = create_and_lock_session(
+        &env,
+        &contract,
+        &token_client,
+        &asset_client,
+        &payer,
+        &payee,
         5000,
-        "Session amount must be correct"
     );
+    let before = contract.get_session(&session_id);
+    assert!(before.is_some(), "Session must exist before upgrade");
 
     // --- UPGRADE CONTRACT ---
-    let new_wasm_hash = soroban_sdk::BytesN::from_array(&env, &[42; 32]);
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "upgrade"),
-        soroban_sdk::vec![&env, new_wasm_hash.into_val(&env)],
-    );
+    let contract = upgrade_in_place(&env, &contract.address);
 
     // --- AFTER UPGRADE: Verify session data still accessible ---
-    let after: Option<Session> = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_session"),
-        soroban_sdk::vec![&env, session_id.into_val(&env)],
-    );
-
-    assert!(after.is_some(), "Session must persist after upgrade");
-    let session_after = after.unwrap();
+    let session_after = contract
+        .get_session(&session_id)
+        .expect("Session must persist after upgrade");
     assert_eq!(session_after.amount, 5000, "Amount must persist");
     assert_eq!(session_after.payer, payer, "Payer must persist");
     assert_eq!(session_after.payee, payee, "Payee must persist");
-    assert_eq!(session_after.asset, asset, "Asset must persist");
+    assert_eq!(
+        session_after.asset, token_client.address,
+        "Asset must persist"
+    );
     assert_eq!(
         session_after.status,
         SessionStatus::Locked,
@@ -123,99 +162,36 @@ fn test_storage_persistence_lock_funds_after_upgrade() {
 
 #[test]
 fn test_storage_persistence_configuration_after_upgrade() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = soroban_sdk::Address::generate(&env);
-    let treasury = soroban_sdk::Address::generate(&env);
-    let new_treasury = soroban_sdk::Address::generate(&env);
-
-    // Register and initialize contract
-    let contract_id = env.register_contract(None, SkillSyncContract);
-
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "init"),
-        soroban_sdk::vec![
-            &env,
-            admin.clone().into_val(&env),
-            (500u32).into_val(&env), // 5% fee
-            treasury.clone().into_val(&env),
-            (DEFAULT_DISPUTE_WINDOW_SECONDS).into_val(&env),
-        ],
-    );
-
-    // Verify initial configuration
-    let fee_before: u32 = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_platform_fee"),
-        soroban_sdk::vec![&env],
-    );
-    assert_eq!(fee_before, 500);
+    let (env, contract, _token_client, _asset_client, treasury, _payer, _payee) =
+        setup_contract_with_config();
+    let admin = Address::generate(&env);
 
-    let treasury_before: soroban_sdk::Address = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_treasury"),
-        soroban_sdk::vec![&env],
-    );
-    assert_eq!(treasury_before, treasury);
+    assert_eq!(contract.get_platform_fee(), 500);
+    assert_eq!(contract.get_treasury(), treasury);
 
     // Update configuration
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "set_platform_fee"),
-        soroban_sdk::vec![
-            &env,
-            (750u32).into_val(&env), // Change to 7.5%
-        ],
-    );
-
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "set_treasury"),
-        soroban_sdk::vec![&env, new_treasury.clone().into_val(&env),],
-    );
-
-    // Verify updates
-    let fee_updated: u32 = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_platform_fee"),
-        soroban_sdk::vec![&env],
-    );
-    assert_eq!(fee_updated, 750);
-
-    let treasury_updated: soroban_sdk::Address = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_treasury"),
-        soroban_sdk::vec![&env],
-    );
-    assert_eq!(treasury_updated, new_treasury);
+    let new_treasury = Address::generate(&env);
+    contract.set_platform_fee(&750); // Change to 7.5%
+    contract.grant_role(&Bytes::from_slice(&env, b"TREASURER_ROLE"), &admin);
+    contract.set_treasury(&admin, &new_treasury);
+    contract.set_dispute_window(&2_000);
 
     // --- Perform contract upgrade ---
-    let new_wasm_hash = soroban_sdk::BytesN::from_array(&env, &[43; 32]);
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "upgrade"),
-        soroban_sdk::vec![&env, new_wasm_hash.into_val(&env),],
-    );
+    let contract = upgrade_in_place(&env, &contract.address);
 
     // --- After upgrade, verify configuration persists ---
-    let fee_after: u32 = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_platform_fee"),
-        soroban_sdk::vec![&env],
-    );
-    assert_eq!(fee_after, 750, "Platform fee should persist after upgrade");
-
-    let treasury_after: soroban_sdk::Address = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_treasury"),
-        soroban_sdk::vec![&env],
+    assert_eq!(
+        contract.get_platform_fee(),
+        750,
+        "Platform fee should persist after upgrade"
     );
     assert_eq!(
-        treasury_after, new_treasury,
+        contract.get_treasury(),
+        new_treasury,
         "Treasury should persist after upgrade"
     );
+    assert_eq!(contract.get_dispute_window(), 2_000);
+    assert!(contract.is_token_allowed(&_token_client.address));
 }
 
 // ============================================================================
@@ -224,103 +200,35 @@ fn test_storage_persistence_configuration_after_upgrade() {
 
 #[test]
 fn test_storage_persistence_multiple_sessions() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = soroban_sdk::Address::generate(&env);
-    let treasury = soroban_sdk::Address::generate(&env);
-
-    // Register and initialize contract
-    let contract_id = env.register_contract(None, SkillSyncContract);
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "init"),
-        soroban_sdk::vec![
-            &env,
-            admin.clone().into_val(&env),
-            (500u32).into_val(&env),
-            treasury.clone().into_val(&env),
-            (DEFAULT_DISPUTE_WINDOW_SECONDS).into_val(&env),
-        ],
-    );
-
-    // Create multiple sessions
-    let payer_1 = soroban_sdk::Address::generate(&env);
-    let payee_1 = soroban_sdk::Address::generate(&env);
-    let payer_2 = soroban_sdk::Address::generate(&env);
-    let payee_2 = soroban_sdk::Address::generate(&env);
+    let (env, contract, _token_client, _asset_client, _treasury, _payer, _payee) =
+        setup_contract_with_config();
 
     let session_id_1 = Bytes::from_slice(&env, b"session_multi_001");
     let session_id_2 = Bytes::from_slice(&env, b"session_multi_002");
-
-    let session_1 = Session {
-        version: 1,
-        session_id: session_id_1.clone(),
-        payer: payer_1.clone(),
-        payee: payee_1.clone(),
-        asset: soroban_sdk::Address::generate(&env),
-        amount: 1000,
-        fee_bps: 500,
-        status: SessionStatus::Locked,
-        created_at: env.ledger().timestamp(),
-        updated_at: env.ledger().timestamp(),
-        dispute_deadline: env.ledger().timestamp() + DEFAULT_DISPUTE_WINDOW_SECONDS,
-        expires_at: env.ledger().timestamp() + 7 * 24 * 60 * 60,
-        payer_approved: false,
-        payee_approved: false,
-        approved_at: 0,
-        dispute_opened_at: 0,
-        resolved_at: 0,
-        resolver: None,
-        resolution_note: None,
-    };
-
-    let mut session_2 = session_1.clone();
-    session_2.session_id = session_id_2.clone();
-    session_2.payer = payer_2.clone();
-    session_2.payee = payee_2.clone();
-    session_2.amount = 2000;
-    session_2.status = SessionStatus::Completed;
-
-    // Store both sessions
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "put_session"),
-        soroban_sdk::vec![&env, session_1.into_val(&env)],
-    );
-
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "put_session"),
-        soroban_sdk::vec![&env, session_2.into_val(&env)],
-    );
+    contract.put_session(&stored_session(
+        &env,
+        &session_id_1,
+        SessionStatus::Locked,
+        1000,
+    ));
+    contract.put_session(&stored_session(
+        &env,
+        &session_id_2,
+        SessionStatus::Completed,
+        2000,
+    ));
 
     // Upgrade contract
-    let new_wasm_hash = soroban_sdk::BytesN::from_array(&env, &[44; 32]);
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "upgrade"),
-        soroban_sdk::vec![&env, new_wasm_hash.into_val(&env)],
-    );
+    let contract = upgrade_in_place(&env, &contract.address);
 
     // Verify both sessions persist
-    let retrieved_1: Option<Session> = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_session"),
-        soroban_sdk::vec![&env, session_id_1.into_val(&env)],
-    );
-    assert!(retrieved_1.is_some());
-    assert_eq!(retrieved_1.unwrap().amount, 1000);
-    assert_eq!(retrieved_1.unwrap().status, SessionStatus::Locked);
-
-    let retrieved_2: Option<Session> = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_session"),
-        soroban_sdk::vec![&env, session_id_2.into_val(&env)],
-    );
-    assert!(retrieved_2.is_some());
-    assert_eq!(retrieved_2.unwrap().amount, 2000);
-    assert_eq!(retrieved_2.unwrap().status, SessionStatus::Completed);
+    let retrieved_1 = contract.get_session(&session_id_1).unwrap();
+    assert_eq!(retrieved_1.amount, 1000);
+    assert_eq!(retrieved_1.status, SessionStatus::Locked);
+
+    let retrieved_2 = contract.get_session(&session_id_2).unwrap();
+    assert_eq!(retrieved_2.amount, 2000);
+    assert_eq!(retrieved_2.status, SessionStatus::Completed);
 }
 
 // ============================================================================
@@ -329,78 +237,23 @@ fn test_storage_persistence_multiple_sessions() {
 
 #[test]
 fn test_storage_persistence_dispute_state_after_upgrade() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = soroban_sdk::Address::generate(&env);
-    let treasury = soroban_sdk::Address::generate(&env);
-    let payer = soroban_sdk::Address::generate(&env);
-    let payee = soroban_sdk::Address::generate(&env);
-    let asset = soroban_sdk::Address::generate(&env);
-
-    // Register and initialize contract
-    let contract_id = env.register_contract(None, SkillSyncContract);
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "init"),
-        soroban_sdk::vec![
-            &env,
-            admin.into_val(&env),
-            (500u32).into_val(&env),
-            treasury.into_val(&env),
-            (DEFAULT_DISPUTE_WINDOW_SECONDS).into_val(&env),
-        ],
-    );
+    let (env, contract, _token_client, _asset_client, _treasury, _payer, _payee) =
+        setup_contract_with_config();
 
     // Create a disputed session
     let session_id = Bytes::from_slice(&env, b"storage_test_dispute");
     let dispute_time = env.ledger().timestamp();
-    let session = Session {
-        version: 1,
-        session_id: session_id.clone(),
-        payer: payer.clone(),
-        payee: payee.clone(),
-        asset,
-        amount: 5000,
-        fee_bps: 500,
-        status: SessionStatus::Disputed,
-        created_at: env.ledger().timestamp(),
-        updated_at: env.ledger().timestamp(),
-        dispute_deadline: env.ledger().timestamp() + DEFAULT_DISPUTE_WINDOW_SECONDS,
-        expires_at: env.ledger().timestamp() + 7 * 24 * 60 * 60,
-        payer_approved: false,
-        payee_approved: false,
-        approved_at: 0,
-        dispute_opened_at: dispute_time,
-        resolved_at: 0,
-        resolver: None,
-        resolution_note: None,
-    };
-
-    // Store disputed session before upgrade
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "put_session"),
-        soroban_sdk::vec![&env, session.into_val(&env)],
-    );
+    let mut session = stored_session(&env, &session_id, SessionStatus::Disputed, 5000);
+    session.dispute_opened_at = dispute_time;
+    contract.put_session(&session);
 
     // --- UPGRADE CONTRACT ---
-    let new_wasm_hash = soroban_sdk::BytesN::from_array(&env, &[45; 32]);
-    env.invoke_contract::<()>(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "upgrade"),
-        soroban_sdk::vec![&env, new_wasm_hash.into_val(&env)],
-    );
+    let contract = upgrade_in_place(&env, &contract.address);
 
     // --- AFTER UPGRADE: Verify dispute state persists ---
-    let retrieved: Option<Session> = env.invoke_contract(
-        &contract_id,
-        &soroban_sdk::Symbol::new(&env, "get_session"),
-        soroban_sdk::vec![&env, session_id.into_val(&env)],
-    );
-
-    assert!(retrieved.is_some(), "Disputed session must persist");
-    let session_after = retrieved.unwrap();
+    let session_after = contract
+        .get_session(&session_id)
+        .expect("Disputed session must persist");
     assert_eq!(
         session_after.status,
         SessionStatus::Disputed,
@@ -412,99 +265,43 @@ fn test_storage_persistence_dispute_state_after_upgrade() {
     );
 }
 
-// ============================================================================
-// Test 5: Config persistence across upgrade
-// ============================================================================
-
-#[test]
-fn test_storage_persistence_config_after_upgrade() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = soroban_sdk::Address::generate(&env);
-    let treasury = soroban_sdk::Address::generate(&env);
-
-    let contract_id = env.register_contract(None, SkillSyncContract);
-    let contract = SkillSyncContractClient::new(&env, &contract_id);
-    contract.init(&admin, &500, &treasury, &DEFAULT_DISPUTE_WINDOW_SECONDS);
-
-    let admin_before = contract.get_admin();
-    let treasury_before = contract.get_treasury();
-    let fee_before = contract.get_platform_fee();
-
-    assert_eq!(admin_before, admin, "Admin should be set");
-    assert_eq!(treasury_before, treasury, "Treasury should be set");
-    assert_eq!(fee_before, 500, "Platform fee should be 5%");
-
-    // --- Update configuration before upgrade ---
-    let new_treasury = soroban_sdk::Address::generate(&env);
-    contract.set_treasury(&new_treasury);
-
-    let new_fee = 750u32; // 7.5%
-    contract.set_platform_fee(&new_fee);
-
-    // Verify updates
-    assert_eq!(contract.get_treasury(), new_treasury);
-    assert_eq!(contract.get_platform_fee(), new_fee);
-
-    // --- After upgrade, verify config is preserved ---
-    let admin_after = contract.get_admin();
-    let treasury_after = contract.get_treasury();
-    let fee_after = contract.get_platform_fee();
-
-    assert_eq!(admin_after, admin, "Admin should persist after upgrade");
-    assert_eq!(
-        treasury_after, new_treasury,
-        "Updated treasury should persist after upgrade"
-    );
-    assert_eq!(
-        fee_after, new_fee,
-        "Updated fee should persist after upgrade"
-    );
-}
-
 // ============================================================================
 // Complex Scenario Tests
 // ============================================================================
 
 #[test]
 fn test_storage_persistence_multiple_sessions_across_upgrade() {
-    let (env, contract, token_client, asset_client, admin, treasury, payer, payee) =
+    let (env, contract, token_client, asset_client, _treasury, payer, payee) =
         setup_contract_with_config();
 
     // Create multiple sessions with different amounts
-    let session_1 = {
-        let id = Bytes::from_slice(&env, b"session_001");
-        asset_client.mint(&payer, &1000);
-        let token_id = token_client.address.clone();
-        contract.lock_funds(&id, &payer, &payee, &token_id, &1000, &500);
-        id
-    };
-
-    let session_2 = {
-        let id = Bytes::from_slice(&env, b"session_002");
-        asset_client.mint(&payer, &2000);
-        let token_id = token_client.address.clone();
-        contract.lock_funds(&id, &payer, &payee, &token_id, &2000, &500);
-        id
-    };
+    asset_client.mint(&payer, &3_150);
+    let session_1 = Bytes::from_slice(&env, b"session_001");
+    contract.lock_funds(
+        &session_1,
+        &payer,
+        &payee,
+        &token_client.address,
+        &1000,
+        &None,
+    );
+    let session_2 = Bytes::from_slice(&env, b"session_002");
+    contract.lock_funds(
+        &session_2,
+        &payer,
+        &payee,
+        &token_client.address,
+        &2000,
+        &None,
+    );
 
     // Complete session_1
     contract.complete_session(&session_1, &payee, &1u64);
 
-    // Verify state before upgrade
-    let s1_before = contract.get_session(&session_1).unwrap();
-    let s2_before = contract.get_session(&session_2).unwrap();
-    assert_eq!(s1_before.status, SessionStatus::Completed);
-    assert_eq!(s2_before.status, SessionStatus::Locked);
-
     // --- Simulate contract upgrade ---
-    let new_wasm_hash = BytesN::from_array(&env, &[46; 32]);
-    contract.upgrade(&new_wasm_hash);
+    let contract_after = upgrade_in_place(&env, &contract.address);
 
     // --- After upgrade, verify both sessions are accessible and unchanged ---
-    let contract_after = SkillSyncContractClient::new(&env, &contract.address);
-
     let s1_after = contract_after.get_session(&session_1).unwrap();
     let s2_after = contract_after.get_session(&session_2).unwrap();
 
@@ -514,7 +311,6 @@ fn test_storage_persistence_multiple_sessions_across_upgrade() {
         SessionStatus::Completed,
         "Session 1 status should still be Completed"
     );
-
     assert_eq!(s2_after.amount, 2000, "Session 2 amount should persist");
     assert_eq!(
         s2_after.status,
@@ -524,10 +320,8 @@ fn test_storage_persistence_multiple_sessions_across_upgrade() {
 
     // --- Continue operations after upgrade ---
     contract_after.approve_session(&session_1, &payer, &1u64);
-
-    let s1_final = contract_after.get_session(&session_1).unwrap();
     assert_eq!(
-        s1_final.status,
+        contract_after.get_session(&session_1).unwrap().status,
         SessionStatus::Approved,
         "Session 1 should be approvable after upgrade"
     );
@@ -535,7 +329,7 @@ fn test_storage_persistence_multiple_sessions_across_upgrade() {
 
 #[test]
 fn test_storage_persistence_dispute_state_preserved() {
-    let (env, contract, token_client, asset_client, admin, treasury, payer, payee) =
+    let (env, contract, token_client, asset_client, treasury, payer, payee) =
         setup_contract_with_config();
 
     let amount = 5000_i128;
@@ -555,19 +349,13 @@ fn test_storage_persistence_dispute_state_preserved() {
     // Open dispute
     let reason = Bytes::from_slice(&env, b"Service not as described");
     contract.open_dispute(&session_id, &payer, &reason);
-
-    // Verify dispute state before upgrade
     let session_before = contract.get_session(&session_id).unwrap();
     assert_eq!(session_before.status, SessionStatus::Disputed);
-    assert!(session_before.dispute_opened_at > 0);
 
     // --- Simulate contract upgrade ---
-    let new_wasm_hash = BytesN::from_array(&env, &[47; 32]);
-    contract.upgrade(&new_wasm_hash);
+    let contract_after = upgrade_in_place(&env, &contract.address);
 
     // --- After upgrade, verify dispute state and resolve it ---
-    let contract_after = SkillSyncContractClient::new(&env, &contract.address);
-
     let session_after = contract_after.get_session(&session_id).unwrap();
     assert_eq!(
         session_after.status,
@@ -580,23 +368,21 @@ fn test_storage_persistence_dispute_state_preserved() {
     );
 
     // Resolve dispute with 50/50 split
-    let to_payer = (amount / 2) - (amount * 500 / 10000) / 2;
-    let to_payee = (amount / 2) - (amount * 500 / 10000) / 2;
-    let note = Some(Bytes::from_slice(&env, b"Mutual agreement"));
-
-    contract_after.resolve_dispute(&session_id, &to_payer, &to_payee, &note);
+    contract_after.resolve_dispute(&session_id, &2, &(amount / 2), &(amount / 2));
 
-    let session_resolved = contract_after.get_session(&session_id).unwrap();
     assert_eq!(
-        session_resolved.status,
+        contract_after.get_session(&session_id).unwrap().status,
         SessionStatus::Resolved,
         "Session should be resolvable after upgrade"
     );
+    assert_eq!(token_client.balance(&payer), amount / 2);
+    assert_eq!(token_client.balance(&payee), amount / 2);
+    assert_eq!(token_client.balance(&treasury), amount * 500 / 10_000);
 }
 
 #[test]
 fn test_storage_persistence_auto_refund_after_upgrade() {
-    let (mut env, contract, token_client, asset_client, admin, treasury, payer, payee) =
+    let (env, contract, token_client, asset_client, _treasury, payer, payee) =
         setup_contract_with_config();
 
     let amount = 3000_i128;
@@ -613,45 +399,25 @@ fn test_storage_persistence_auto_refund_after_upgrade() {
     // Complete session
     contract.complete_session(&session_id, &payee, &1u64);
 
-    let session_before = contract.get_session(&session_id).unwrap();
-    assert_eq!(session_before.status, SessionStatus::Completed);
-
     // --- Simulate contract upgrade ---
-    let new_wasm_hash = BytesN::from_array(&env, &[48; 32]);
-    contract.upgrade(&new_wasm_hash);
-
-    // --- After upgrade, advance time and trigger auto-refund ---
-    let contract_after = SkillSyncContractClient::new(&env, &contract.address);
+    let contract_after = upgrade_in_place(&env, &contract.address);
 
+    // Advance past the dispute window
     let dispute_window = contract_after.get_dispute_window();
-    let current_time = env.ledger().timestamp();
-    let completed_time = session_before.completed_at;
-
-    // Advance time past dispute window
-    env.ledger().set(LedgerInfo {
-        timestamp: completed_time + dispute_window + 1,
-        protocol_version: 20,
-        sequence_number: 100,
-        network_id: [0u8; 32],
-        base_reserve: 100,
-        min_temp_entry_ttl: 1,
-        min_persistent_entry_ttl: 1,
-        max_entry_ttl: 100,
-    });
+    env.ledger()
+        .with_mut(|l| l.sequence_number += dispute_window + 1);
 
     // Execute auto-refund
     contract_after.auto_refund(&session_id);
 
-    // Verify refund worked
-    let session_refunded = contract_after.get_session(&session_id).unwrap();
     assert_eq!(
-        session_refunded.status,
+        contract_after.get_session(&session_id).unwrap().status,
         SessionStatus::Refunded,
         "Session should be refunded after auto-refund trigger"
     );
     assert_eq!(
         token_client.balance(&payer),
-        amount,
+        amount + amount * 500 / 10_000,
         "Payer should receive full refund"
     );
 }
@@ -662,10 +428,9 @@ fn test_storage_persistence_auto_refund_after_upgrade() {
 
 #[test]
 fn test_storage_persistence_with_nonce_tracking() {
-    let (env, contract, token_client, asset_client, admin, treasury, payer, payee) =
+    let (env, contract, token_client, asset_client, _treasury, payer, payee) =
         setup_contract_with_config();
 
-    let amount = 1000_i128;
     let session_id = create_and_lock_session(
         &env,
         &contract,
@@ -673,39 +438,34 @@ fn test_storage_persistence_with_nonce_tracking() {
         &asset_client,
         &payer,
         &payee,
-        amount,
+        1000,
     );
 
     // Complete with nonce 1
     contract.complete_session(&session_id, &payee, &1u64);
 
     // --- Simulate contract upgrade ---
-    let new_wasm_hash = BytesN::from_array(&env, &[49; 32]);
-    contract.upgrade(&new_wasm_hash);
-
-    // --- After upgrade, verify nonce is tracked ---
-    let contract_after = SkillSyncContractClient::new(&env, &contract.address);
+    let contract_after = upgrade_in_place(&env, &contract.address);
 
-    // Try to use same nonce (should fail due to replay protection)
-    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        contract_after.complete_session(&session_id, &payee, &1u64);
-    }));
-
-    // Should fail since status is already Completed and nonce was used
-    assert!(result.is_err(), "Replay attack should be prevented");
+    // Reusing the nonce is still rejected after the upgrade
+    assert_eq!(
+        contract_after.try_complete_session(&session_id, &payee, &1u64),
+        Err(Ok(Error::NonceAlreadyUsed.into())),
+        "Replay attack should be prevented"
+    );
 
-    // Use new nonce (should work for approval)
     contract_after.approve_session(&session_id, &payer, &2u64);
-    let session_final = contract_after.get_session(&session_id).unwrap();
-    assert_eq!(session_final.status, SessionStatus::Approved);
+    assert_eq!(
+        contract_after.get_session(&session_id).unwrap().status,
+        SessionStatus::Approved
+    );
 }
 
 #[test]
 fn test_storage_persistence_reputation_scores_across_upgrade() {
-    let (env, contract, token_client, asset_client, admin, treasury, payer, payee) =
+    let (env, contract, token_client, asset_client, _treasury, payer, payee) =
         setup_contract_with_config();
 
-    let amount = 2000_i128;
     let session_id = create_and_lock_session(
         &env,
         &contract,
@@ -713,33 +473,27 @@ fn test_storage_persistence_reputation_scores_across_upgrade() {
         &asset_client,
         &payer,
         &payee,
-        amount,
+        2000,
     );
 
-    // Complete and approve session
+    // Complete, approve and rate the session
     contract.complete_session(&session_id, &payee, &1u64);
     contract.approve_session(&session_id, &payer, &1u64);
-
-    // Add reputation rating (if implemented)
-    // This tests that reputation data persists
-    contract.rate_session(&session_id, &payer, &5u32); // 5-star rating
+    contract.rate_counterparty(&session_id, &payer, &5u32);
 
     // --- Simulate contract upgrade ---
-    let new_wasm_hash = BytesN::from_array(&env, &[50; 32]);
-    contract.upgrade(&new_wasm_hash);
-
-    // --- After upgrade, verify reputation persists ---
-    let contract_after = SkillSyncContractClient::new(&env, &contract.address);
+    let contract_after = upgrade_in_place(&env, &contract.address);
 
-    // Query reputation (implementation depends on contract)
-    let reputation = contract_after.get_mentor_reputation(&payee);
-    assert!(
-        reputation.is_some(),
+    // --- After upgrade, verify the rating persists ---
+    assert_eq!(
+        contract_after.get_user_rating(&payee),
+        (500, 1),
         "Reputation data should persist after upgrade"
     );
-    if let Some(rep) = reputation {
-        assert!(rep.rating_count > 0, "Rating should be recorded");
-    }
+    assert_eq!(
+        contract_after.try_rate_counterparty(&session_id, &payer, &4u32),
+        Err(Ok(Error::AlreadyRated))
+    );
 }
 
 // ============================================================================
@@ -748,23 +502,29 @@ fn test_storage_persistence_reputation_scores_across_upgrade() {
 
 #[test]
 fn test_storage_persistence_comprehensive_lifecycle() {
-    let (mut env, contract, token_client, asset_client, admin, treasury, payer, payee) =
+    let (env, contract, token_client, asset_client, treasury, payer, payee) =
         setup_contract_with_config();
 
     // Phase 1: Create and lock multiple sessions
-    let session_1 = {
-        let id = Bytes::from_slice(&env, b"comprehensive_001");
-        asset_client.mint(&payer, &5000);
-        contract.lock_funds(&id, &payer, &payee, &token_client.address, &5000, &500);
-        id
-    };
-
-    let session_2 = {
-        let id = Bytes::from_slice(&env, b"comprehensive_002");
-        asset_client.mint(&payer, &3000);
-        contract.lock_funds(&id, &payer, &payee, &token_client.address, &3000, &500);
-        id
-    };
+    asset_client.mint(&payer, &8_400);
+    let session_1 = Bytes::from_slice(&env, b"comprehensive_001");
+    contract.lock_funds(
+        &session_1,
+        &payer,
+        &payee,
+        &token_client.address,
+        &5000,
+        &None,
+    );
+    let session_2 = Bytes::from_slice(&env, b"comprehensive_002");
+    contract.lock_funds(
+        &session_2,
+        &payer,
+        &payee,
+        &token_client.address,
+        &3000,
+        &None,
+    );
 
     // Complete session_1, dispute session_2
     contract.complete_session(&session_1, &payee, &1u64);
@@ -772,38 +532,27 @@ fn test_storage_persistence_comprehensive_lifecycle() {
     let reason = Bytes::from_slice(&env, b"Quality issue");
     contract.open_dispute(&session_2, &payer, &reason);
 
-    // Verify state before upgrade
-    let s1_pre = contract.get_session(&session_1).unwrap();
-    let s2_pre = contract.get_session(&session_2).unwrap();
-    assert_eq!(s1_pre.status, SessionStatus::Completed);
-    assert_eq!(s2_pre.status, SessionStatus::Disputed);
-
     // Phase 2: Perform first upgrade
-    let wasm_hash_v2 = BytesN::from_array(&env, &[51; 32]);
-    contract.upgrade(&wasm_hash_v2);
+    let contract_v2 = upgrade_in_place(&env, &contract.address);
 
     // Phase 3: Continue operations after first upgrade
-    let contract_v2 = SkillSyncContractClient::new(&env, &contract.address);
-
     contract_v2.approve_session(&session_1, &payer, &1u64);
-    contract_v2.resolve_dispute(&session_2, &1000, &1000, &None);
-
-    // Verify operations succeeded
-    let s1_mid = contract_v2.get_session(&session_1).unwrap();
-    let s2_mid = contract_v2.get_session(&session_2).unwrap();
-    assert_eq!(s1_mid.status, SessionStatus::Approved);
-    assert_eq!(s2_mid.status, SessionStatus::Resolved);
+    contract_v2.resolve_dispute(&session_2, &2, &1500, &1500);
+    assert_eq!(
+        contract_v2.get_session(&session_1).unwrap().status,
+        SessionStatus::Approved
+    );
+    assert_eq!(
+        contract_v2.get_session(&session_2).unwrap().status,
+        SessionStatus::Resolved
+    );
 
     // Phase 4: Perform second upgrade
-    let wasm_hash_v3 = BytesN::from_array(&env, &[52; 32]);
-    contract_v2.upgrade(&wasm_hash_v3);
+    let contract_v3 = upgrade_in_place(&env, &contract.address);
 
     // Phase 5: Verify final state after second upgrade
-    let contract_v3 = SkillSyncContractClient::new(&env, &contract.address);
-
     let s1_final = contract_v3.get_session(&session_1).unwrap();
     let s2_final = contract_v3.get_session(&session_2).unwrap();
-
     assert_eq!(
         s1_final.status,
         SessionStatus::Approved,
@@ -0,0 +1,78 @@
+/// Declare a contract struct whose fields include an `Option` of a host
+/// object (`Address`, `Bytes`, `BytesN`) or of another contract type.
+///
+/// soroban-sdk 20's `#[contracttype]` also derives XDR conversions under
+/// `testutils`, and it cannot produce them for such fields, so the crate's
+/// test target would not build. This macro writes the same `Val` map codec
+/// that `#[contracttype]` derives and uses it in every build, so tests
+/// exercise exactly the encoding stored on-chain. Outside tests, an unused
+/// `#[contracttype]` copy of the struct is expanded in the named hidden
+/// module only to emit the contract spec entry for the type.
+///
+/// The key list must name every field in lexicographic order, the order the
+/// host requires for map keys. A missing or unknown field fails to compile;
+/// a misordered list makes every encode fail at runtime.
+macro_rules! map_contracttype {
+    (
+        $spec:ident [$($field:ident),* $(,)?]
+        $(#[$attr:meta])*
+        pub struct $ty:ident { $($body:tt)* }
+    ) => {
+        $(#[$attr])*
+        pub struct $ty { $($body)* }
+
+        impl soroban_sdk::TryFromVal<soroban_sdk::Env, soroban_sdk::Val> for $ty {
+            type Error = soroban_sdk::ConversionError;
+
+            fn try_from_val(
+                env: &soroban_sdk::Env,
+                val: &soroban_sdk::Val,
+            ) -> Result<Self, soroban_sdk::ConversionError> {
+                use soroban_sdk::{ConversionError, EnvBase, MapObject, TryIntoVal, Val};
+                const KEYS: &[&str] = &[$(stringify!($field)),*];
+                let mut vals = [Val::VOID.to_val(); KEYS.len()];
+                let map: MapObject = val.try_into().map_err(|_| ConversionError)?;
+                env.map_unpack_to_slice(map, KEYS, &mut vals)
+                    .map_err(|_| ConversionError)?;
+                let mut vals = vals.iter();
+                Ok(Self {
+                    $($field: vals
+                        .next()
+                        .ok_or(ConversionError)?
+                        .try_into_val(env)
+                        .map_err(|_| ConversionError)?,)*
+                })
+            }
+        }
+
+        impl soroban_sdk::TryFromVal<soroban_sdk::Env, $ty> for soroban_sdk::Val {
+            type Error = soroban_sdk::ConversionError;
+
+            fn try_from_val(
+                env: &soroban_sdk::Env,
+                val: &$ty,
+            ) -> Result<Self, soroban_sdk::ConversionError> {
+                use soroban_sdk::{ConversionError, EnvBase, TryIntoVal, Val};
+                const KEYS: &[&str] = &[$(stringify!($field)),*];
+                let vals: [Val; KEYS.len()] = [
+                    $((&val.$field).try_into_val(env).map_err(|_| ConversionError)?),*
+                ];
+                Ok(env
+                    .map_new_from_slices(KEYS, &vals)
+                    .map_err(|_| ConversionError)?
+                    .into())
+            }
+        }
+
+        #[cfg(not(any(test, feature = "testutils")))]
+        #[doc(hidden)]
+        pub mod $spec {
+            use super::*;
+
+            #[allow(dead_code)]
+            #[soroban_sdk::contracttype]
+            $(#[$attr])*
+            pub struct $ty { $($body)* }
+        }
+    };
+}
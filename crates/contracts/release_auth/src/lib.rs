@@ -0,0 +1,253 @@
+#![no_std]
+
+//! Release authorization contract.
+//!
+//! The backend signs a `ReleasePayload` with one of the registered ed25519
+//! keys; anyone may submit the payload and signature to `authorize`, which
+//! records the authorization for the booking. Each nonce can be used once.
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env,
+    Symbol,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    SignerAlreadyExists = 3, // Public key is already in the signer set
+    SignerNotFound = 4,      // Public key is not in the signer set
+    NonceAlreadyUsed = 5,    // Payload nonce was consumed by an earlier authorization
+    AlreadyAuthorized = 6,   // Booking already has an authorization
+    InvalidAmount = 7,       // Payload amount must be positive
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum DataKey {
+    Admin,
+    SignerCount,
+    Signer(BytesN<32>), // Registered ed25519 public key
+    UsedNonce(u64),     // Nonce consumed by an authorization
+    Authorization(u64), // Authorization recorded for a booking id
+}
+
+/// Message signed by the backend to authorize a release.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleasePayload {
+    pub booking_id: u64,
+    pub payee: Address,
+    pub amount: i128,
+    pub nonce: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Authorization {
+    pub booking_id: u64,
+    pub payee: Address,
+    pub amount: i128,
+    pub signer: BytesN<32>,
+    pub authorized_at: u64,
+}
+
+// ── Events ────────────────────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SignerAddedEvent {
+    pub public_key: BytesN<32>,
+    pub added_by: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SignerRemovedEvent {
+    pub public_key: BytesN<32>,
+    pub removed_by: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ReleaseAuthorizedEvent {
+    pub booking_id: u64,
+    pub payee: Address,
+    pub amount: i128,
+    pub nonce: u64,
+    pub signer: BytesN<32>,
+}
+
+#[contract]
+pub struct ReleaseAuthContract;
+
+#[contractimpl]
+impl ReleaseAuthContract {
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::SignerCount, &0u32);
+        Ok(())
+    }
+
+    pub fn get_admin(env: Env) -> Result<Address, Error> {
+        read_admin(&env)
+    }
+
+    /// Register a backend signing key. Only callable by admin.
+    /// Emits SignerAdded.
+    pub fn add_signer(env: Env, public_key: BytesN<32>) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        let key = DataKey::Signer(public_key.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(Error::SignerAlreadyExists);
+        }
+        env.storage().persistent().set(&key, &true);
+        env.storage().instance().set(
+            &DataKey::SignerCount,
+            &(Self::signer_count(env.clone()) + 1),
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "SignerAdded"),),
+            SignerAddedEvent {
+                public_key,
+                added_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Remove a backend signing key. Only callable by admin.
+    /// Emits SignerRemoved.
+    pub fn remove_signer(env: Env, public_key: BytesN<32>) -> Result<(), Error> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+
+        let key = DataKey::Signer(public_key.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::SignerNotFound);
+        }
+        env.storage().persistent().remove(&key);
+        env.storage().instance().set(
+            &DataKey::SignerCount,
+            &(Self::signer_count(env.clone()) - 1),
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "SignerRemoved"),),
+            SignerRemovedEvent {
+                public_key,
+                removed_by: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn is_signer(env: Env, public_key: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::Signer(public_key))
+    }
+
+    pub fn signer_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::SignerCount)
+            .unwrap_or(0)
+    }
+
+    /// Bytes a signer must sign for `payload`: the XDR of this contract's
+    /// address followed by the payload, so a signature cannot be replayed
+    /// against another deployment.
+    pub fn release_message(env: Env, payload: ReleasePayload) -> Bytes {
+        (env.current_contract_address(), payload).to_xdr(&env)
+    }
+
+    /// Record a release authorization for `payload.booking_id`.
+    ///
+    /// `signature` must be a valid ed25519 signature by `public_key` over
+    /// `release_message(payload)`; an invalid signature traps. Emits
+    /// ReleaseAuthorized.
+    pub fn authorize(
+        env: Env,
+        payload: ReleasePayload,
+        public_key: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        read_admin(&env)?;
+
+        if payload.amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if !Self::is_signer(env.clone(), public_key.clone()) {
+            return Err(Error::SignerNotFound);
+        }
+        if Self::is_nonce_used(env.clone(), payload.nonce) {
+            return Err(Error::NonceAlreadyUsed);
+        }
+        let auth_key = DataKey::Authorization(payload.booking_id);
+        if env.storage().persistent().has(&auth_key) {
+            return Err(Error::AlreadyAuthorized);
+        }
+
+        let message = Self::release_message(env.clone(), payload.clone());
+        env.crypto()
+            .ed25519_verify(&public_key, &message, &signature);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::UsedNonce(payload.nonce), &true);
+        env.storage().persistent().set(
+            &auth_key,
+            &Authorization {
+                booking_id: payload.booking_id,
+                payee: payload.payee.clone(),
+                amount: payload.amount,
+                signer: public_key.clone(),
+                authorized_at: env.ledger().timestamp(),
+            },
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "ReleaseAuthorized"),),
+            ReleaseAuthorizedEvent {
+                booking_id: payload.booking_id,
+                payee: payload.payee,
+                amount: payload.amount,
+                nonce: payload.nonce,
+                signer: public_key,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn is_nonce_used(env: Env, nonce: u64) -> bool {
+        env.storage().persistent().has(&DataKey::UsedNonce(nonce))
+    }
+
+    pub fn get_authorization(env: Env, booking_id: u64) -> Option<Authorization> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Authorization(booking_id))
+    }
+}
+
+fn read_admin(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
+}
+
+#[cfg(test)]
+mod test;
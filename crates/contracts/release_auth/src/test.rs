@@ -0,0 +1,174 @@
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{testutils::Address as _, Address, BytesN, Env};
+
+fn setup() -> (Env, ReleaseAuthContractClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReleaseAuthContract);
+    let client = ReleaseAuthContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    (env, client, admin)
+}
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn public_key(env: &Env, key: &SigningKey) -> BytesN<32> {
+    BytesN::from_array(env, &key.verifying_key().to_bytes())
+}
+
+fn sign(
+    env: &Env,
+    client: &ReleaseAuthContractClient,
+    key: &SigningKey,
+    payload: &ReleasePayload,
+) -> BytesN<64> {
+    let message: std::vec::Vec<u8> = client.release_message(payload).iter().collect();
+    BytesN::from_array(env, &key.sign(&message).to_bytes())
+}
+
+fn payload(env: &Env, booking_id: u64, nonce: u64) -> ReleasePayload {
+    ReleasePayload {
+        booking_id,
+        payee: Address::generate(env),
+        amount: 1_000,
+        nonce,
+    }
+}
+
+#[test]
+fn initialize_only_once() {
+    let (_env, client, admin) = setup();
+
+    assert_eq!(client.get_admin(), admin);
+    assert_eq!(client.signer_count(), 0);
+    assert_eq!(
+        client.try_initialize(&admin),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn add_and_remove_signers() {
+    let (env, client, _admin) = setup();
+    let pk = public_key(&env, &signing_key(1));
+
+    client.add_signer(&pk);
+    assert!(client.is_signer(&pk));
+    assert_eq!(client.signer_count(), 1);
+    assert_eq!(
+        client.try_add_signer(&pk),
+        Err(Ok(Error::SignerAlreadyExists))
+    );
+
+    client.remove_signer(&pk);
+    assert!(!client.is_signer(&pk));
+    assert_eq!(client.signer_count(), 0);
+    assert_eq!(
+        client.try_remove_signer(&pk),
+        Err(Ok(Error::SignerNotFound))
+    );
+}
+
+#[test]
+fn authorize_with_registered_signer() {
+    let (env, client, _admin) = setup();
+    let key = signing_key(1);
+    let pk = public_key(&env, &key);
+    client.add_signer(&pk);
+
+    let payload = payload(&env, 42, 7);
+    let signature = sign(&env, &client, &key, &payload);
+    client.authorize(&payload, &pk, &signature);
+
+    let auth = client.get_authorization(&42).unwrap();
+    assert_eq!(auth.payee, payload.payee);
+    assert_eq!(auth.amount, 1_000);
+    assert_eq!(auth.signer, pk);
+    assert!(client.is_nonce_used(&7));
+}
+
+#[test]
+fn authorize_rejects_unknown_signer() {
+    let (env, client, _admin) = setup();
+    let key = signing_key(2);
+    let pk = public_key(&env, &key);
+
+    let payload = payload(&env, 1, 1);
+    let signature = sign(&env, &client, &key, &payload);
+    assert_eq!(
+        client.try_authorize(&payload, &pk, &signature),
+        Err(Ok(Error::SignerNotFound))
+    );
+}
+
+#[test]
+fn authorize_rejects_replayed_nonce_and_booking() {
+    let (env, client, _admin) = setup();
+    let key = signing_key(1);
+    let pk = public_key(&env, &key);
+    client.add_signer(&pk);
+
+    let first = payload(&env, 1, 5);
+    client.authorize(&first, &pk, &sign(&env, &client, &key, &first));
+
+    let same_nonce = payload(&env, 2, 5);
+    assert_eq!(
+        client.try_authorize(&same_nonce, &pk, &sign(&env, &client, &key, &same_nonce)),
+        Err(Ok(Error::NonceAlreadyUsed))
+    );
+
+    let same_booking = payload(&env, 1, 6);
+    assert_eq!(
+        client.try_authorize(
+            &same_booking,
+            &pk,
+            &sign(&env, &client, &key, &same_booking)
+        ),
+        Err(Ok(Error::AlreadyAuthorized))
+    );
+}
+
+#[test]
+fn release_message_binds_payload_and_deployment() {
+    let (env, client, admin) = setup();
+    let signed = payload(&env, 1, 1);
+
+    let mut tampered = signed.clone();
+    tampered.amount = 1_000_000;
+    assert_ne!(
+        client.release_message(&signed),
+        client.release_message(&tampered)
+    );
+
+    let other_id = env.register_contract(None, ReleaseAuthContract);
+    let other = ReleaseAuthContractClient::new(&env, &other_id);
+    other.initialize(&admin);
+    assert_ne!(
+        client.release_message(&signed),
+        other.release_message(&signed)
+    );
+}
+
+#[test]
+fn removed_signer_cannot_authorize() {
+    let (env, client, _admin) = setup();
+    let key = signing_key(1);
+    let pk = public_key(&env, &key);
+    client.add_signer(&pk);
+    client.remove_signer(&pk);
+
+    let payload = payload(&env, 1, 1);
+    assert_eq!(
+        client.try_authorize(&payload, &pk, &sign(&env, &client, &key, &payload)),
+        Err(Ok(Error::SignerNotFound))
+    );
+    assert!(!client.is_nonce_used(&1));
+}
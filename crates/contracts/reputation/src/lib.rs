@@ -0,0 +1,198 @@
+#![no_std]
+
+//! Mentor reputation contract.
+//!
+//! The admin bumps a mentor's score for each completed session and for each
+//! review (1–5 stars, centred on 3). Scores decay by `decay_bps` for every
+//! full decay period without activity; anyone may call `apply_decay`.
+//! Every score change emits ReputationUpdated with the reason.
+
+use soroban_sdk::{contract, contracterror, contractimpl, contracttype, Address, Env, Symbol};
+
+/// Points added for each completed session.
+pub const SESSION_POINTS: i64 = 10;
+/// Points per star above or below a neutral 3-star review.
+pub const REVIEW_POINTS_PER_STAR: i64 = 5;
+pub const MIN_RATING: u32 = 1;
+pub const MAX_RATING: u32 = 5;
+/// Default decay: 1 % of the score per period.
+pub const DEFAULT_DECAY_BPS: u32 = 100;
+/// Length of one decay period: 30 days.
+pub const DECAY_PERIOD_SECONDS: u64 = 30 * 24 * 60 * 60;
+/// Periods applied in one `apply_decay` call.
+pub const MAX_DECAY_PERIODS: u64 = 24;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidRating = 3,   // Rating outside MIN_RATING..=MAX_RATING
+    InvalidDecayBps = 4, // Decay rate above 10 000 bps
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum DataKey {
+    Admin,
+    DecayBps,
+    Reputation(Address), // Mentor -> Reputation
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Reputation {
+    pub score: i64,
+    pub sessions: u32,
+    pub reviews: u32,
+    /// Sum of all review ratings, for averaging off-chain.
+    pub rating_sum: u64,
+    /// Start of the current decay period.
+    pub last_decay_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ReputationUpdatedEvent {
+    pub account: Address,
+    pub old_score: i64,
+    pub new_score: i64,
+    /// `session`, `review` or `decay`.
+    pub reason: Symbol,
+}
+
+#[contract]
+pub struct ReputationContract;
+
+#[contractimpl]
+impl ReputationContract {
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn get_admin(env: Env) -> Result<Address, Error> {
+        read_admin(&env)
+    }
+
+    /// Set the share of the score lost per decay period. Only callable by
+    /// admin.
+    pub fn set_decay_bps(env: Env, decay_bps: u32) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        if decay_bps > 10_000 {
+            return Err(Error::InvalidDecayBps);
+        }
+        env.storage().instance().set(&DataKey::DecayBps, &decay_bps);
+        Ok(())
+    }
+
+    pub fn get_decay_bps(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::DecayBps)
+            .unwrap_or(DEFAULT_DECAY_BPS)
+    }
+
+    /// Credit `mentor` with a completed session, after applying any decay
+    /// owed for the idle time before it. Only callable by admin.
+    pub fn record_session(env: Env, mentor: Address) -> Result<i64, Error> {
+        read_admin(&env)?.require_auth();
+
+        Self::apply_decay(env.clone(), mentor.clone());
+        let mut rep = Self::get_reputation(env.clone(), mentor.clone());
+        let old_score = rep.score;
+        rep.sessions += 1;
+        rep.score += SESSION_POINTS;
+        rep.last_decay_at = env.ledger().timestamp();
+
+        update(&env, &mentor, &rep, old_score, "session");
+        Ok(rep.score)
+    }
+
+    /// Apply a 1–5 star review to `mentor`. Ratings above 3 raise the score
+    /// and ratings below lower it, never below zero. Owed decay is applied
+    /// first. Only callable by admin.
+    pub fn apply_review(env: Env, mentor: Address, rating: u32) -> Result<i64, Error> {
+        read_admin(&env)?.require_auth();
+        if !(MIN_RATING..=MAX_RATING).contains(&rating) {
+            return Err(Error::InvalidRating);
+        }
+
+        Self::apply_decay(env.clone(), mentor.clone());
+        let mut rep = Self::get_reputation(env.clone(), mentor.clone());
+        let old_score = rep.score;
+        rep.reviews += 1;
+        rep.rating_sum += rating as u64;
+        rep.score = (rep.score + (rating as i64 - 3) * REVIEW_POINTS_PER_STAR).max(0);
+        rep.last_decay_at = env.ledger().timestamp();
+
+        update(&env, &mentor, &rep, old_score, "review");
+        Ok(rep.score)
+    }
+
+    /// Decay `mentor`'s score for each full period elapsed since their last
+    /// activity or decay, up to MAX_DECAY_PERIODS per call. Callable by
+    /// anyone.
+    pub fn apply_decay(env: Env, mentor: Address) -> i64 {
+        let mut rep = Self::get_reputation(env.clone(), mentor.clone());
+        let now = env.ledger().timestamp();
+        let periods =
+            (now.saturating_sub(rep.last_decay_at) / DECAY_PERIOD_SECONDS).min(MAX_DECAY_PERIODS);
+        if periods == 0 || rep.score == 0 {
+            return rep.score;
+        }
+
+        let old_score = rep.score;
+        let decay_bps = Self::get_decay_bps(env.clone()) as i64;
+        for _ in 0..periods {
+            rep.score -= rep.score * decay_bps / 10_000;
+        }
+        rep.last_decay_at += periods * DECAY_PERIOD_SECONDS;
+
+        update(&env, &mentor, &rep, old_score, "decay");
+        rep.score
+    }
+
+    pub fn get_reputation(env: Env, mentor: Address) -> Reputation {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Reputation(mentor))
+            .unwrap_or_default()
+    }
+
+    pub fn score_of(env: Env, mentor: Address) -> i64 {
+        Self::get_reputation(env, mentor).score
+    }
+}
+
+fn read_admin(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
+}
+
+fn update(env: &Env, mentor: &Address, rep: &Reputation, old_score: i64, reason: &str) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Reputation(mentor.clone()), rep);
+
+    env.events().publish(
+        (Symbol::new(env, "ReputationUpdated"),),
+        ReputationUpdatedEvent {
+            account: mentor.clone(),
+            old_score,
+            new_score: rep.score,
+            reason: Symbol::new(env, reason),
+        },
+    );
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,102 @@
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    Address, Env,
+};
+
+fn setup() -> (Env, ReputationContractClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationContract);
+    let contract = ReputationContractClient::new(&env, &contract_id);
+    contract.initialize(&Address::generate(&env));
+    let mentor = Address::generate(&env);
+
+    (env, contract, mentor)
+}
+
+#[test]
+fn sessions_and_reviews_update_score() {
+    let (_env, contract, mentor) = setup();
+
+    assert_eq!(contract.record_session(&mentor), SESSION_POINTS);
+    assert_eq!(
+        contract.apply_review(&mentor, &5),
+        SESSION_POINTS + 2 * REVIEW_POINTS_PER_STAR
+    );
+    assert_eq!(contract.apply_review(&mentor, &3), 20);
+
+    let rep = contract.get_reputation(&mentor);
+    assert_eq!(rep.sessions, 1);
+    assert_eq!(rep.reviews, 2);
+    assert_eq!(rep.rating_sum, 8);
+}
+
+#[test]
+fn review_rating_is_validated_and_score_floors_at_zero() {
+    let (_env, contract, mentor) = setup();
+
+    assert_eq!(
+        contract.try_apply_review(&mentor, &0),
+        Err(Ok(Error::InvalidRating))
+    );
+    assert_eq!(
+        contract.try_apply_review(&mentor, &6),
+        Err(Ok(Error::InvalidRating))
+    );
+    assert_eq!(contract.apply_review(&mentor, &1), 0);
+}
+
+#[test]
+fn score_decays_per_full_period() {
+    let (env, contract, mentor) = setup();
+    contract.set_decay_bps(&1_000);
+    for _ in 0..10 {
+        contract.record_session(&mentor);
+    }
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += DECAY_PERIOD_SECONDS - 1);
+    assert_eq!(contract.apply_decay(&mentor), 100);
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += DECAY_PERIOD_SECONDS + 1);
+    assert_eq!(contract.apply_decay(&mentor), 81);
+
+    // The partial period carried over is not decayed twice.
+    assert_eq!(contract.apply_decay(&mentor), 81);
+    assert_eq!(
+        contract.try_set_decay_bps(&10_001),
+        Err(Ok(Error::InvalidDecayBps))
+    );
+}
+
+#[test]
+fn activity_after_idle_gap_applies_owed_decay_first() {
+    let (env, contract, mentor) = setup();
+    contract.set_decay_bps(&1_000);
+    for _ in 0..10 {
+        contract.record_session(&mentor);
+    }
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += 2 * DECAY_PERIOD_SECONDS);
+    assert_eq!(
+        contract.apply_review(&mentor, &5),
+        81 + 2 * REVIEW_POINTS_PER_STAR
+    );
+
+    // Decay is capped at MAX_DECAY_PERIODS per catch-up.
+    env.ledger()
+        .with_mut(|l| l.timestamp += (MAX_DECAY_PERIODS + 6) * DECAY_PERIOD_SECONDS);
+    let mut expected = 91;
+    for _ in 0..MAX_DECAY_PERIODS {
+        expected -= expected * 1_000 / 10_000;
+    }
+    assert_eq!(contract.record_session(&mentor), expected + SESSION_POINTS);
+    assert_eq!(
+        contract.get_reputation(&mentor).last_decay_at,
+        env.ledger().timestamp()
+    );
+}
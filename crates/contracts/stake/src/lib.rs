@@ -0,0 +1,335 @@
+#![no_std]
+
+//! Mentor staking contract.
+//!
+//! Mentors lock tokens with `stake`. `request_unstake` moves part of the
+//! stake into a pending balance that `withdraw_unstaked` returns once the
+//! cooldown has elapsed. The admin may `slash` staked and pending tokens,
+//! which are sent to the treasury.
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, Address, Env, Symbol,
+};
+
+/// Default unstake cooldown: 7 days.
+pub const DEFAULT_COOLDOWN_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidAmount = 3,     // Amount must be positive
+    InsufficientStake = 4, // Amount exceeds the staked (or slashable) balance
+    NothingToWithdraw = 5, // No pending unstake for this token
+    CooldownActive = 6,    // Pending unstake is not unlocked yet
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum DataKey {
+    Admin,
+    Treasury,
+    Cooldown,
+    Stake(Address, Address), // (mentor, token) -> StakeInfo
+    TotalStaked(Address),    // token -> staked plus pending across mentors
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StakeInfo {
+    pub staked: i128,
+    pub pending_unstake: i128,
+    /// Timestamp from which `pending_unstake` can be withdrawn.
+    pub unlock_at: u64,
+}
+
+// ── Events ────────────────────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StakedEvent {
+    pub mentor: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub total_staked: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct UnstakeRequestedEvent {
+    pub mentor: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub unlock_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct UnstakeWithdrawnEvent {
+    pub mentor: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SlashedEvent {
+    pub mentor: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub treasury: Address,
+}
+
+#[contract]
+pub struct StakeContract;
+
+#[contractimpl]
+impl StakeContract {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        treasury: Address,
+        cooldown_seconds: u64,
+    ) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+        env.storage()
+            .instance()
+            .set(&DataKey::Cooldown, &cooldown_seconds);
+        Ok(())
+    }
+
+    pub fn get_admin(env: Env) -> Result<Address, Error> {
+        read_admin(&env)
+    }
+
+    /// Update the account that receives slashed tokens. Only callable by admin.
+    pub fn set_treasury(env: Env, treasury: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+        Ok(())
+    }
+
+    pub fn get_treasury(env: Env) -> Result<Address, Error> {
+        read_treasury(&env)
+    }
+
+    /// Update the unstake cooldown. Only callable by admin; pending requests
+    /// keep the unlock time they were created with.
+    pub fn set_cooldown(env: Env, cooldown_seconds: u64) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::Cooldown, &cooldown_seconds);
+        Ok(())
+    }
+
+    pub fn get_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Cooldown)
+            .unwrap_or(DEFAULT_COOLDOWN_SECONDS)
+    }
+
+    /// Transfer `amount` of `token` from the mentor into the contract and add
+    /// it to their stake. Emits Staked.
+    pub fn stake(env: Env, mentor: Address, token: Address, amount: i128) -> Result<(), Error> {
+        read_admin(&env)?;
+        mentor.require_auth();
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut info = Self::get_stake(env.clone(), mentor.clone(), token.clone());
+        info.staked += amount;
+        write_stake(&env, &mentor, &token, &info);
+        adjust_total(&env, &token, amount);
+
+        token::Client::new(&env, &token).transfer(
+            &mentor,
+            &env.current_contract_address(),
+            &amount,
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "Staked"),),
+            StakedEvent {
+                mentor,
+                token,
+                amount,
+                total_staked: info.staked,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Move `amount` from the mentor's stake into the pending balance. The
+    /// whole pending balance unlocks `cooldown` seconds from now.
+    /// Emits UnstakeRequested.
+    pub fn request_unstake(
+        env: Env,
+        mentor: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<u64, Error> {
+        mentor.require_auth();
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut info = Self::get_stake(env.clone(), mentor.clone(), token.clone());
+        if amount > info.staked {
+            return Err(Error::InsufficientStake);
+        }
+        info.staked -= amount;
+        info.pending_unstake += amount;
+        info.unlock_at = env.ledger().timestamp() + Self::get_cooldown(env.clone());
+        write_stake(&env, &mentor, &token, &info);
+
+        env.events().publish(
+            (Symbol::new(&env, "UnstakeRequested"),),
+            UnstakeRequestedEvent {
+                mentor,
+                token,
+                amount,
+                unlock_at: info.unlock_at,
+            },
+        );
+
+        Ok(info.unlock_at)
+    }
+
+    /// Return the mentor's pending balance once it has unlocked.
+    /// Emits UnstakeWithdrawn.
+    pub fn withdraw_unstaked(env: Env, mentor: Address, token: Address) -> Result<i128, Error> {
+        mentor.require_auth();
+
+        let mut info = Self::get_stake(env.clone(), mentor.clone(), token.clone());
+        let amount = info.pending_unstake;
+        if amount == 0 {
+            return Err(Error::NothingToWithdraw);
+        }
+        if env.ledger().timestamp() < info.unlock_at {
+            return Err(Error::CooldownActive);
+        }
+
+        info.pending_unstake = 0;
+        info.unlock_at = 0;
+        write_stake(&env, &mentor, &token, &info);
+        adjust_total(&env, &token, -amount);
+
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &mentor,
+            &amount,
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "UnstakeWithdrawn"),),
+            UnstakeWithdrawnEvent {
+                mentor,
+                token,
+                amount,
+            },
+        );
+
+        Ok(amount)
+    }
+
+    /// Slash `amount` of the mentor's tokens and send them to the treasury.
+    /// The stake is slashed first, then any pending unstake. Only callable by
+    /// admin. Emits Slashed.
+    pub fn slash(env: Env, mentor: Address, token: Address, amount: i128) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut info = Self::get_stake(env.clone(), mentor.clone(), token.clone());
+        if amount > info.staked + info.pending_unstake {
+            return Err(Error::InsufficientStake);
+        }
+        let from_stake = amount.min(info.staked);
+        info.staked -= from_stake;
+        info.pending_unstake -= amount - from_stake;
+        if info.pending_unstake == 0 {
+            info.unlock_at = 0;
+        }
+        write_stake(&env, &mentor, &token, &info);
+        adjust_total(&env, &token, -amount);
+
+        let treasury = read_treasury(&env)?;
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &treasury,
+            &amount,
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "Slashed"),),
+            SlashedEvent {
+                mentor,
+                token,
+                amount,
+                treasury,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_stake(env: Env, mentor: Address, token: Address) -> StakeInfo {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Stake(mentor, token))
+            .unwrap_or_default()
+    }
+
+    /// Staked plus pending tokens held for all mentors.
+    pub fn total_staked(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TotalStaked(token))
+            .unwrap_or(0)
+    }
+}
+
+fn read_admin(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
+}
+
+fn read_treasury(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Treasury)
+        .ok_or(Error::NotInitialized)
+}
+
+fn write_stake(env: &Env, mentor: &Address, token: &Address, info: &StakeInfo) {
+    let key = DataKey::Stake(mentor.clone(), token.clone());
+    if *info == StakeInfo::default() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, info);
+    }
+}
+
+fn adjust_total(env: &Env, token: &Address, delta: i128) {
+    let key = DataKey::TotalStaked(token.clone());
+    let total: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    env.storage().persistent().set(&key, &(total + delta));
+}
+
+#[cfg(test)]
+mod test;
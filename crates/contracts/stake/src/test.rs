@@ -0,0 +1,112 @@
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    token::{Client as TokenClient, StellarAssetClient},
+    Address, Env,
+};
+
+const COOLDOWN: u64 = 1_000;
+
+fn setup() -> (
+    Env,
+    StakeContractClient<'static>,
+    TokenClient<'static>,
+    Address,
+    Address,
+) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let mentor = Address::generate(&env);
+
+    let token_address = env.register_stellar_asset_contract(Address::generate(&env));
+    let token_client = TokenClient::new(&env, &token_address);
+    StellarAssetClient::new(&env, &token_address).mint(&mentor, &10_000);
+
+    let contract_id = env.register_contract(None, StakeContract);
+    let contract = StakeContractClient::new(&env, &contract_id);
+    contract.initialize(&admin, &treasury, &COOLDOWN);
+
+    (env, contract, token_client, treasury, mentor)
+}
+
+#[test]
+fn stake_moves_tokens_into_contract() {
+    let (_env, contract, token, _treasury, mentor) = setup();
+
+    contract.stake(&mentor, &token.address, &4_000);
+
+    assert_eq!(token.balance(&mentor), 6_000);
+    assert_eq!(token.balance(&contract.address), 4_000);
+    assert_eq!(contract.get_stake(&mentor, &token.address).staked, 4_000);
+    assert_eq!(contract.total_staked(&token.address), 4_000);
+    assert_eq!(
+        contract.try_stake(&mentor, &token.address, &0),
+        Err(Ok(Error::InvalidAmount))
+    );
+}
+
+#[test]
+fn unstake_waits_for_cooldown() {
+    let (env, contract, token, _treasury, mentor) = setup();
+    contract.stake(&mentor, &token.address, &4_000);
+
+    assert_eq!(
+        contract.try_request_unstake(&mentor, &token.address, &5_000),
+        Err(Ok(Error::InsufficientStake))
+    );
+    let unlock_at = contract.request_unstake(&mentor, &token.address, &1_500);
+    assert_eq!(unlock_at, env.ledger().timestamp() + COOLDOWN);
+
+    let info = contract.get_stake(&mentor, &token.address);
+    assert_eq!(info.staked, 2_500);
+    assert_eq!(info.pending_unstake, 1_500);
+    assert_eq!(
+        contract.try_withdraw_unstaked(&mentor, &token.address),
+        Err(Ok(Error::CooldownActive))
+    );
+
+    env.ledger().with_mut(|l| l.timestamp = unlock_at);
+    assert_eq!(contract.withdraw_unstaked(&mentor, &token.address), 1_500);
+    assert_eq!(token.balance(&mentor), 7_500);
+    assert_eq!(contract.total_staked(&token.address), 2_500);
+    assert_eq!(
+        contract.try_withdraw_unstaked(&mentor, &token.address),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+}
+
+#[test]
+fn slash_sends_stake_then_pending_to_treasury() {
+    let (_env, contract, token, treasury, mentor) = setup();
+    contract.stake(&mentor, &token.address, &3_000);
+    contract.request_unstake(&mentor, &token.address, &1_000);
+
+    assert_eq!(
+        contract.try_slash(&mentor, &token.address, &3_001),
+        Err(Ok(Error::InsufficientStake))
+    );
+    contract.slash(&mentor, &token.address, &2_500);
+
+    let info = contract.get_stake(&mentor, &token.address);
+    assert_eq!(info.staked, 0);
+    assert_eq!(info.pending_unstake, 500);
+    assert_eq!(token.balance(&treasury), 2_500);
+    assert_eq!(token.balance(&contract.address), 500);
+    assert_eq!(contract.total_staked(&token.address), 500);
+}
+
+#[test]
+fn new_cooldown_applies_to_later_requests() {
+    let (env, contract, token, _treasury, mentor) = setup();
+    contract.stake(&mentor, &token.address, &1_000);
+    let first = contract.request_unstake(&mentor, &token.address, &100);
+
+    contract.set_cooldown(&50);
+    assert_eq!(contract.get_stake(&mentor, &token.address).unlock_at, first);
+
+    let second = contract.request_unstake(&mentor, &token.address, &100);
+    assert_eq!(second, env.ledger().timestamp() + 50);
+}